@@ -0,0 +1,214 @@
+use std::rc::Rc;
+
+use crate::{
+    expr::Expr,
+    stmt::{Function, Stmt},
+    token_type::Object,
+    traits::{ExprVisitor, StmtVisitor},
+};
+
+// renders the tree as s-expressions, one top-level statement per line,
+// e.g. `print 1 + 2 * 3;` becomes `(print (+ 1 (* 2 3)))`
+#[derive(Debug, Default)]
+pub struct AstPrinter;
+
+impl AstPrinter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn print(&mut self, stmts: &[Stmt]) -> String {
+        stmts
+            .iter()
+            .map(|stmt| stmt.accept(self))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn print_expr(&mut self, expr: &Expr) -> String {
+        expr.accept(self)
+    }
+
+    fn parenthesize(&mut self, name: &str, exprs: &[&Expr]) -> String {
+        let mut parts = vec![name.to_string()];
+        parts.extend(exprs.iter().map(|expr| expr.accept(self)));
+        format!("({})", parts.join(" "))
+    }
+
+    fn block(&mut self, name: &str, stmts: &[Stmt]) -> String {
+        let mut parts = vec![name.to_string()];
+        parts.extend(stmts.iter().map(|stmt| stmt.accept(self)));
+        format!("({})", parts.join(" "))
+    }
+
+    fn function(&mut self, keyword: &str, function: &Function) -> String {
+        let params = function
+            .params
+            .iter()
+            .map(|param| param.lexeme.clone())
+            .collect::<Vec<String>>();
+        let name = format!("{keyword} {} ({})", function.name.lexeme, params.join(" "));
+        self.block(&name, &function.body)
+    }
+}
+
+impl ExprVisitor<String> for AstPrinter {
+    fn visit_binary(&mut self, expr: &crate::expr::Binary) -> String {
+        self.parenthesize(&expr.token.lexeme, &[&expr.left, &expr.right])
+    }
+
+    fn visit_grouping(&mut self, expr: &crate::expr::Grouping) -> String {
+        self.parenthesize("group", &[&expr.expression])
+    }
+
+    fn visit_unary(&mut self, expr: &crate::expr::Unary) -> String {
+        self.parenthesize(&expr.token.lexeme, &[&expr.right])
+    }
+
+    fn visit_literal(&mut self, expr: &crate::expr::Literal) -> String {
+        match &expr.object {
+            Object::String(s) => format!("{s:?}"),
+            object => object.to_string(),
+        }
+    }
+
+    fn visit_variable(&mut self, expr: &crate::expr::Variable) -> String {
+        expr.name.lexeme.clone()
+    }
+
+    fn visit_assign(&mut self, expr: &crate::expr::Assign) -> String {
+        self.parenthesize(&format!("= {}", expr.name.lexeme), &[&expr.value])
+    }
+
+    fn visit_logical(&mut self, expr: &crate::expr::Logical) -> String {
+        self.parenthesize(&expr.operator.lexeme, &[&expr.left, &expr.right])
+    }
+
+    fn visit_call(&mut self, expr: &crate::expr::Call) -> String {
+        let mut exprs = vec![&expr.callee];
+        exprs.extend(expr.arguments.iter());
+        self.parenthesize("call", &exprs)
+    }
+
+    fn visit_get(&mut self, expr: &crate::expr::Get) -> String {
+        let object = expr.object.accept(self);
+        format!("(. {} {})", object, expr.name.lexeme)
+    }
+
+    fn visit_set(&mut self, expr: &crate::expr::Set) -> String {
+        let object = expr.object.accept(self);
+        let value = expr.value.accept(self);
+        format!("(= (. {} {}) {})", object, expr.name.lexeme, value)
+    }
+
+    fn visit_this(&mut self, _expr: &crate::expr::This) -> String {
+        "this".to_string()
+    }
+
+    fn visit_super(&mut self, expr: &crate::expr::Super) -> String {
+        format!("(. super {})", expr.method.lexeme)
+    }
+
+    fn visit_list(&mut self, expr: &crate::expr::List) -> String {
+        let elements = expr.elements.iter().collect::<Vec<&Expr>>();
+        self.parenthesize("list", &elements)
+    }
+
+    fn visit_index(&mut self, expr: &crate::expr::Index) -> String {
+        self.parenthesize("[]", &[&expr.object, &expr.index])
+    }
+
+    fn visit_set_index(&mut self, expr: &crate::expr::SetIndex) -> String {
+        let target = self.parenthesize("[]", &[&expr.object, &expr.index]);
+        let value = expr.value.accept(self);
+        format!("(= {target} {value})")
+    }
+}
+
+impl StmtVisitor<String> for AstPrinter {
+    fn visit_print(&mut self, stmt: &crate::stmt::Print) -> String {
+        self.parenthesize("print", &[&stmt.expression])
+    }
+
+    fn visit_expression(&mut self, stmt: &crate::stmt::Expression) -> String {
+        self.parenthesize(";", &[&stmt.expression])
+    }
+
+    fn visit_var(&mut self, stmt: &crate::stmt::Var) -> String {
+        let name = format!("var {}", stmt.token.lexeme);
+        match &stmt.initializer {
+            Some(initializer) => self.parenthesize(&name, &[initializer]),
+            None => format!("({name})"),
+        }
+    }
+
+    fn visit_block(&mut self, stmt: &crate::stmt::Block) -> String {
+        self.block("block", &stmt.statements)
+    }
+
+    fn visit_if(&mut self, stmt: &crate::stmt::If) -> String {
+        let condition = stmt.condition.accept(self);
+        let then_branch = stmt.then_branch.accept(self);
+        match &stmt.else_branch {
+            Some(else_branch) => {
+                let else_branch = else_branch.accept(self);
+                format!("(if {condition} {then_branch} {else_branch})")
+            }
+            None => format!("(if {condition} {then_branch})"),
+        }
+    }
+
+    fn visit_while(&mut self, stmt: &crate::stmt::While) -> String {
+        let condition = stmt.condition.accept(self);
+        let body = stmt.body.accept(self);
+        format!("(while {condition} {body})")
+    }
+
+    // clauses that were left out print as `_`
+    fn visit_for(&mut self, stmt: &crate::stmt::For) -> String {
+        let initializer = match &stmt.initializer {
+            Some(initializer) => initializer.accept(self),
+            None => "_".to_string(),
+        };
+        let condition = match &stmt.condition {
+            Some(condition) => condition.accept(self),
+            None => "_".to_string(),
+        };
+        let increment = match &stmt.increment {
+            Some(increment) => increment.accept(self),
+            None => "_".to_string(),
+        };
+        let body = stmt.body.accept(self);
+        format!("(for {initializer} {condition} {increment} {body})")
+    }
+
+    fn visit_function(&mut self, stmt: &Rc<Function>) -> String {
+        self.function("fun", stmt)
+    }
+
+    fn visit_return(&mut self, stmt: &crate::stmt::Return) -> String {
+        match &stmt.value {
+            Some(value) => self.parenthesize("return", &[value]),
+            None => "(return)".to_string(),
+        }
+    }
+
+    fn visit_class(&mut self, stmt: &crate::stmt::Class) -> String {
+        let mut parts = vec![format!("class {}", stmt.name.lexeme)];
+        if let Some(superclass) = &stmt.superclass {
+            parts.push(format!("< {}", superclass.name.lexeme));
+        }
+        for method in &stmt.methods {
+            parts.push(self.function("method", method));
+        }
+        format!("({})", parts.join(" "))
+    }
+
+    fn visit_break(&mut self, _stmt: &crate::stmt::Break) -> String {
+        "(break)".to_string()
+    }
+
+    fn visit_continue(&mut self, _stmt: &crate::stmt::Continue) -> String {
+        "(continue)".to_string()
+    }
+}
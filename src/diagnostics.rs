@@ -0,0 +1,79 @@
+use std::fmt;
+
+use crate::error::Error;
+
+// errors gathered over one run of the pipeline, owned by whoever is running it
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    errors: Vec<Error>,
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = self
+            .errors
+            .iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+impl From<Error> for Diagnostics {
+    fn from(err: Error) -> Self {
+        Self { errors: vec![err] }
+    }
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn report(&mut self, err: Error) {
+        self.errors.push(err);
+    }
+
+    pub fn extend(&mut self, errs: Vec<Error>) {
+        self.errors.extend(errs);
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    pub fn take(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+
+    // the earliest stage that failed decides how the run failed, a run
+    // without any diagnostics failing at all would be a bug of our own
+    pub fn exit_code(&self) -> i32 {
+        self.errors.first().map_or(70, Error::exit_code)
+    }
+
+    pub fn render(&self, source: &str, script: &str) -> String {
+        self.errors
+            .iter()
+            .map(|err| {
+                let trace = &err.inner().trace;
+                if trace.is_empty() {
+                    err.render(source)
+                } else {
+                    format!(
+                        "{}\n{}",
+                        err.render(source),
+                        err.inner().render_trace(script)
+                    )
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
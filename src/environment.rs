@@ -4,22 +4,8 @@ use crate::{error::LoxError, lox_error, token::Token, token_type::Object};
 
 #[derive(Debug)]
 pub struct Environment {
-    pub enclosing: Option<Rc<Environment>>,
-    pub values: RefCell<HashMap<String, Object>>,
-}
-
-impl Clone for Environment {
-    fn clone(&self) -> Self {
-        Self {
-            enclosing: self.enclosing.clone(),
-            values: self.values.clone(),
-        }
-    }
-
-    fn clone_from(&mut self, source: &Self) {
-        self.enclosing = source.enclosing.clone();
-        self.values = source.values.clone();
-    }
+    pub enclosing: Option<Rc<RefCell<Environment>>>,
+    pub values: HashMap<String, Object>,
 }
 
 impl Environment {
@@ -27,60 +13,68 @@ impl Environment {
     pub fn initialize() -> Self {
         Self {
             enclosing: None,
-            values: RefCell::new(HashMap::<String, Object>::new()),
+            values: HashMap::<String, Object>::new(),
         }
     }
 
     // create local_scope
-    pub fn from(enclosing: Environment) -> Self {
+    pub fn from(enclosing: Rc<RefCell<Environment>>) -> Self {
         Self {
-            enclosing: Some(Rc::new(enclosing)),
-            values: RefCell::new(HashMap::<String, Object>::new()),
+            enclosing: Some(enclosing),
+            values: HashMap::<String, Object>::new(),
         }
     }
 
     pub fn define(&mut self, name: String, value: Object) {
-        // self.values.insert(name, value);
-        self.values.borrow_mut().insert(name, value);
+        self.values.insert(name, value);
     }
 
     pub fn get(&self, name: &Token) -> Result<Object, LoxError> {
-        // if self.values.contains_key(&name.lexeme) {
-        //     Ok(self.values.get(&name.lexeme).unwrap().clone())
-        // } else if self.enclosing.is_some() {
-        //     self.enclosing.clone().unwrap().get(name)
-        // } else {
-        //     Err(lox_error!(name.clone(), "Undefined variable."))
-        // }
-        match self.values.borrow_mut().get(&name.lexeme) {
+        match self.values.get(&name.lexeme) {
             Some(v) => Ok(v.clone()),
             None => match &self.enclosing {
-                Some(env) => env.get(name),
+                Some(env) => env.borrow().get(name),
                 None => Err(lox_error!(name.clone(), "Undefined variable.")),
             },
         }
     }
 
-    pub fn assign(&self, name: &Token, value: Object) -> Result<(), LoxError> {
-        // if self.values.contains_key(&name.lexeme) {
-        //     self.values.insert(name.lexeme.clone(), value);
-        //     Ok(())
-        // } else if self.enclosing.is_some() {
-        //     // self.enclosing.clone().unwrap().assign(name, value)
-        //     self.enclosing
-        //         .borrow_mut()
-        //         .clone()
-        //         .unwrap()
-        //         .assign(name, value)
-        // } else {
-        //     Err(lox_error!(name.clone(), "Undefined variable."))
-        // }
-        if self.values.borrow_mut().contains_key(&name.lexeme) {
-            self.values.borrow_mut().insert(name.lexeme.clone(), value);
+    pub fn get_at(&self, distance: usize, name: &Token) -> Result<Object, LoxError> {
+        if distance == 0 {
+            return match self.values.get(&name.lexeme) {
+                Some(v) => Ok(v.clone()),
+                None => Err(lox_error!(name.clone(), "Undefined variable.")),
+            };
+        }
+        match &self.enclosing {
+            Some(env) => env.borrow().get_at(distance - 1, name),
+            None => Err(lox_error!(name.clone(), "Undefined variable.")),
+        }
+    }
+
+    pub fn assign(&mut self, name: &Token, value: Object) -> Result<(), LoxError> {
+        if let Some(slot) = self.values.get_mut(&name.lexeme) {
+            *slot = value;
+            return Ok(());
+        }
+        match &self.enclosing {
+            Some(env) => env.borrow_mut().assign(name, value),
+            None => Err(lox_error!(name.clone(), "Undefined variable.")),
+        }
+    }
+
+    pub fn assign_at(
+        &mut self,
+        distance: usize,
+        name: &Token,
+        value: Object,
+    ) -> Result<(), LoxError> {
+        if distance == 0 {
+            self.values.insert(name.lexeme.clone(), value);
             return Ok(());
         }
         match &self.enclosing {
-            Some(env) => env.assign(name, value),
+            Some(env) => env.borrow_mut().assign_at(distance - 1, name, value),
             None => Err(lox_error!(name.clone(), "Undefined variable.")),
         }
     }
@@ -1,76 +1,243 @@
 use core::fmt;
 
-use crate::{token::Token, token_type::TokenType, ERROR};
+use crate::{
+    token::{self, Span, Token},
+    token_type::{Object, TokenType},
+};
+
+// a diagnostic, tagged with the stage of the pipeline that rejected the
+// script. each stage wraps the errors it raises itself
+#[derive(Debug, Clone)]
+pub enum Error {
+    Scan(LoxError),
+    Parse(LoxError),
+    Resolve(LoxError),
+    Runtime(LoxError),
+}
+
+impl Error {
+    // stable identifiers, safe for tooling to match on
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Scan(_) => "E0001",
+            Self::Parse(_) => "E0002",
+            Self::Resolve(_) => "E0003",
+            Self::Runtime(_) => "E0004",
+        }
+    }
+
+    // sysexits: EX_DATAERR for a bad script, EX_SOFTWARE for one that crashed
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Scan(_) | Self::Parse(_) | Self::Resolve(_) => 65,
+            Self::Runtime(_) => 70,
+        }
+    }
+
+    pub fn inner(&self) -> &LoxError {
+        match self {
+            Self::Scan(err) | Self::Parse(err) | Self::Resolve(err) | Self::Runtime(err) => err,
+        }
+    }
+
+    pub fn into_inner(self) -> LoxError {
+        match self {
+            Self::Scan(err) | Self::Parse(err) | Self::Resolve(err) | Self::Runtime(err) => err,
+        }
+    }
+
+    // the rendered error tagged with its code, e.g.
+    //   error[E0002]: [line 3] Error at ';': Expect expression
+    pub fn render(&self, source: &str) -> String {
+        format!("error[{}]: {}", self.code(), self.inner().render(source))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inner())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.inner())
+    }
+}
 
 #[derive(Debug, Clone, Default)]
 pub struct LoxError {
     pub line: usize,
     pub where_error: String,
     pub msg: String,
+    pub span: Option<Span>,
+    // the source ran out before it was valid, so more input may still fix it
+    pub at_end: bool,
+    // innermost call first, empty for static errors
+    pub trace: Box<[StackFrame]>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub function: String,
+    pub line: usize,
+    // the source the line is in, see token::source_id
+    pub source: u64,
+    // that source's name, when the host gave it one
+    pub script: Option<String>,
+}
+
+impl StackFrame {
+    pub fn new(function: &str, line: usize, source: u64) -> Self {
+        Self {
+            function: function.to_string(),
+            line,
+            source,
+            script: None,
+        }
+    }
 }
 
 impl fmt::Display for LoxError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "[line {}] Error {}: {}",
-            self.line, self.where_error, self.msg
-        )
+        if self.where_error.is_empty() {
+            write!(f, "[line {}] Error: {}", self.line, self.msg)
+        } else {
+            write!(
+                f,
+                "[line {}] Error {}: {}",
+                self.line, self.where_error, self.msg
+            )
+        }
     }
 }
 
+impl std::error::Error for LoxError {}
+
 impl LoxError {
     pub fn new(line: usize, where_err: &str, msg: &str) -> Self {
         Self {
             line,
             where_error: where_err.to_string(),
             msg: msg.to_string(),
+            span: None,
+            at_end: false,
+            trace: Box::default(),
         }
     }
 
     pub fn from(token: Token, msg: &str) -> Self {
-        if token.token_type == TokenType::Eof {
-            Self::new(token.line, " at end ", msg)
+        let mut err = if token.token_type == TokenType::Eof {
+            Self::new(token.line, "at end", msg)
         } else {
-            Self::new(token.line, format!(" at '{}' ", token.lexeme).as_str(), msg)
-        }
+            Self::new(token.line, format!("at '{}'", token.lexeme).as_str(), msg)
+        };
+        err.at_end = token.token_type == TokenType::Eof;
+        err.with_span(token.span())
+    }
+
+    // raised by a native function, which has no token of its own. the call
+    // site fills in where it happened
+    pub fn native(msg: &str) -> Self {
+        Self::new(0, "", msg)
     }
 
-    pub fn set_err_global(&self, bool: bool) {
-        let mut error = ERROR.lock().unwrap();
-        *error = bool;
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    // the message followed by the offending source line, underlined like
+    //   3 | print a + ;
+    //     |           ^
+    // just the message when the span points into some other source, such as
+    // a function declared on an earlier repl line
+    pub fn render(&self, source: &str) -> String {
+        let mut out = self.to_string();
+        let span = match self.span {
+            Some(span)
+                if span.source == token::source_id(source)
+                    && source.is_char_boundary(span.offset) =>
+            {
+                span
+            }
+            _ => return out,
+        };
+
+        let line_start = source[..span.offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[span.offset..]
+            .find('\n')
+            .map_or(source.len(), |i| span.offset + i);
+        let text = source[line_start..line_end].trim_end_matches('\r');
+
+        // keep tabs so the caret lines up with the source above it
+        let padding = source[line_start..span.offset]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let remaining = source[span.offset..line_end].chars().count();
+        let width = span.length.min(remaining).max(1);
+
+        let gutter = " ".repeat(self.line.to_string().len());
+        out.push_str(&format!("\n {} | {}", self.line, text));
+        out.push_str(&format!(
+            "\n {} | {}^{}",
+            gutter,
+            padding,
+            "~".repeat(width - 1)
+        ));
+        out
     }
 
-    pub fn report(&self) -> Self {
-        self.set_err_global(true);
-        self.clone()
+    // one `at function (script:line)` per frame, innermost first. frames from
+    // a source without a name of its own are put down to `script`. runs of the
+    // same frame, as left by runaway recursion, are cut short after a few
+    pub fn render_trace(&self, script: &str) -> String {
+        const REPEATS_SHOWN: usize = 3;
+
+        let mut lines = Vec::new();
+        let mut rest = &self.trace[..];
+        while let Some(frame) = rest.first() {
+            let run = rest.iter().take_while(|other| *other == frame).count();
+            let name = frame.script.as_deref().unwrap_or(script);
+            let line = format!("    at {} ({}:{})", frame.function, name, frame.line);
+            lines.extend(std::iter::repeat_n(line, run.min(REPEATS_SHOWN)));
+            if run > REPEATS_SHOWN {
+                lines.push(format!(
+                    "    ... previous frame repeated {} more times",
+                    run - REPEATS_SHOWN
+                ));
+            }
+            rest = &rest[run..];
+        }
+        lines.join("\n")
     }
+}
+
+// non-local exits that travel up through statement execution
+#[derive(Debug, Clone)]
+pub enum Unwind {
+    Error(LoxError),
+    Return(Object),
+    Break,
+    Continue,
+}
 
-    pub fn clear() {
-        let mut error = ERROR.lock().unwrap();
-        *error = false;
+impl From<LoxError> for Unwind {
+    fn from(err: LoxError) -> Self {
+        Self::Error(err)
     }
 }
 
 #[macro_export]
 macro_rules! lox_error {
     () => {
-        LoxError::default()
+        $crate::error::LoxError::default()
     };
     ($line: expr, $where_err: expr, $msg: expr) => {
-        LoxError::new($line, $where_err, $msg)
-    };
-    ($token: expr, $msg: expr) => {
-        LoxError::from($token, $msg)
-    };
-}
-
-#[macro_export]
-macro_rules! lox_error_report {
-    ($line: expr, $where_err: expr, $msg: expr) => {
-        LoxError::new($line, $where_err, $msg).report()
+        $crate::error::LoxError::new($line, $where_err, $msg)
     };
     ($token: expr, $msg: expr) => {
-        LoxError::from($token, $msg).report()
+        $crate::error::LoxError::from($token, $msg)
     };
 }
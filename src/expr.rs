@@ -1,7 +1,17 @@
-use std::rc::Rc;
+use std::{
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use crate::{token::Token, token_type::Object, traits::ExprVisitor};
 
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+// identifies a variable reference for the resolver, survives clones of the tree
+fn next_id() -> usize {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
 #[derive(Debug, Clone)]
 pub enum Expr {
     Binary(Box<Binary>),
@@ -11,6 +21,14 @@ pub enum Expr {
     Variable(Rc<Variable>),
     Assign(Box<Assign>),
     Logical(Box<Logical>),
+    Call(Box<Call>),
+    Get(Box<Get>),
+    Set(Box<Set>),
+    This(Rc<This>),
+    Super(Rc<Super>),
+    List(Box<List>),
+    Index(Box<Index>),
+    SetIndex(Box<SetIndex>),
 }
 
 impl Expr {
@@ -23,6 +41,14 @@ impl Expr {
             Self::Variable(expr) => visitor.visit_variable(expr),
             Self::Assign(expr) => visitor.visit_assign(expr),
             Self::Logical(expr) => visitor.visit_logical(expr),
+            Self::Call(expr) => visitor.visit_call(expr),
+            Self::Get(expr) => visitor.visit_get(expr),
+            Self::Set(expr) => visitor.visit_set(expr),
+            Self::This(expr) => visitor.visit_this(expr),
+            Self::Super(expr) => visitor.visit_super(expr),
+            Self::List(expr) => visitor.visit_list(expr),
+            Self::Index(expr) => visitor.visit_index(expr),
+            Self::SetIndex(expr) => visitor.visit_set_index(expr),
         }
     }
 }
@@ -76,24 +102,33 @@ impl Literal {
 
 #[derive(Debug, Clone)]
 pub struct Variable {
+    pub id: usize,
     pub name: Token,
 }
 
 impl Variable {
     pub fn new(name: Token) -> Rc<Self> {
-        Rc::new(Self { name })
+        Rc::new(Self {
+            id: next_id(),
+            name,
+        })
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Assign {
+    pub id: usize,
     pub name: Token,
     pub value: Expr,
 }
 
 impl Assign {
     pub fn new(name: Token, value: Expr) -> Box<Self> {
-        Box::new(Self { name, value })
+        Box::new(Self {
+            id: next_id(),
+            name,
+            value,
+        })
     }
 }
 
@@ -113,3 +148,129 @@ impl Logical {
         })
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub callee: Expr,
+    pub paren: Token,
+    pub arguments: Vec<Expr>,
+}
+
+impl Call {
+    pub fn new(callee: Expr, paren: Token, arguments: Vec<Expr>) -> Box<Self> {
+        Box::new(Self {
+            callee,
+            paren,
+            arguments,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Get {
+    pub object: Expr,
+    pub name: Token,
+}
+
+impl Get {
+    pub fn new(object: Expr, name: Token) -> Box<Self> {
+        Box::new(Self { object, name })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Set {
+    pub object: Expr,
+    pub name: Token,
+    pub value: Expr,
+}
+
+impl Set {
+    pub fn new(object: Expr, name: Token, value: Expr) -> Box<Self> {
+        Box::new(Self {
+            object,
+            name,
+            value,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct This {
+    pub id: usize,
+    pub keyword: Token,
+}
+
+impl This {
+    pub fn new(keyword: Token) -> Rc<Self> {
+        Rc::new(Self {
+            id: next_id(),
+            keyword,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Super {
+    pub id: usize,
+    pub keyword: Token,
+    pub method: Token,
+}
+
+impl Super {
+    pub fn new(keyword: Token, method: Token) -> Rc<Self> {
+        Rc::new(Self {
+            id: next_id(),
+            keyword,
+            method,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct List {
+    pub elements: Vec<Expr>,
+}
+
+impl List {
+    pub fn new(elements: Vec<Expr>) -> Box<Self> {
+        Box::new(Self { elements })
+    }
+}
+
+// bracket is the closing `]`, where indexing errors are reported
+#[derive(Debug, Clone)]
+pub struct Index {
+    pub object: Expr,
+    pub bracket: Token,
+    pub index: Expr,
+}
+
+impl Index {
+    pub fn new(object: Expr, bracket: Token, index: Expr) -> Box<Self> {
+        Box::new(Self {
+            object,
+            bracket,
+            index,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SetIndex {
+    pub object: Expr,
+    pub bracket: Token,
+    pub index: Expr,
+    pub value: Expr,
+}
+
+impl SetIndex {
+    pub fn new(object: Expr, bracket: Token, index: Expr, value: Expr) -> Box<Self> {
+        Box::new(Self {
+            object,
+            bracket,
+            index,
+            value,
+        })
+    }
+}
@@ -0,0 +1,467 @@
+use std::rc::Rc;
+
+use crate::{
+    expr::Expr,
+    lox::{self, Error},
+    scanner::Scanner,
+    stmt::{Function, Stmt},
+    token::Token,
+    traits::{ExprVisitor, StmtVisitor},
+};
+
+const INDENT: &str = "  ";
+
+// re-emits a script in canonical style. the tree is walked in source order
+// while a cursor follows the scanned tokens, which tells us where every
+// token came from so comments and blank lines can be put back around them.
+#[derive(Debug)]
+pub struct Formatter {
+    tokens: Vec<Token>,
+    comments: Vec<Token>,
+    // next token and comment still to be written
+    current: usize,
+    comment: usize,
+    out: String,
+    indent: usize,
+    // a comment split an expression, the rest of it is indented once more
+    continuation: bool,
+    at_line_start: bool,
+    // last source line written, comments and multi-line strings included
+    last_line: usize,
+}
+
+impl Formatter {
+    // scripts that don't parse are left alone
+    pub fn format(source: &str) -> Result<String, Error> {
+        let mut scanner = Scanner::new(source).with_comments();
+        let stmts = lox::parse_scanned(&mut scanner, false)?;
+
+        let mut formatter = Self {
+            tokens: scanner.tokens,
+            comments: scanner.comments,
+            current: 0,
+            comment: 0,
+            out: String::new(),
+            indent: 0,
+            continuation: false,
+            at_line_start: true,
+            last_line: 0,
+        };
+        for stmt in &stmts {
+            formatter.statement(stmt);
+            formatter.newline();
+        }
+        formatter.comments_before(usize::MAX);
+        Ok(formatter.out)
+    }
+
+    fn statement(&mut self, stmt: &Stmt) {
+        stmt.accept(self);
+    }
+
+    fn expression(&mut self, expr: &Expr) {
+        expr.accept(self);
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.at_line_start {
+            let depth = self.indent + usize::from(self.continuation);
+            self.out.push_str(&INDENT.repeat(depth));
+            self.at_line_start = false;
+        }
+        self.out.push_str(text);
+    }
+
+    fn space(&mut self) {
+        if !self.at_line_start && !self.out.ends_with(' ') {
+            self.out.push(' ');
+        }
+    }
+
+    fn line_break(&mut self) {
+        self.out.push('\n');
+        self.at_line_start = true;
+    }
+
+    // one blank line in the source survives, runs of them collapse
+    fn blank_line(&mut self, line: usize) {
+        if self.at_line_start
+            && line > self.last_line + 1
+            && !self.out.is_empty()
+            && !self.out.ends_with("{\n")
+        {
+            self.out.push('\n');
+        }
+    }
+
+    fn next_offset(&self) -> usize {
+        self.tokens
+            .get(self.current)
+            .map_or(usize::MAX, |token| token.offset)
+    }
+
+    fn token(&mut self, text: &str) {
+        let token = match self.tokens.get(self.current) {
+            Some(token) => token.clone(),
+            None => return self.write(text),
+        };
+        debug_assert_eq!(token.lexeme, text, "formatter out of step with the source");
+
+        self.comments_before(token.offset);
+        if text != "}" {
+            self.blank_line(token.line);
+        }
+        self.write(text);
+        self.last_line = token.line + token.lexeme.matches('\n').count();
+        self.current += 1;
+    }
+
+    // written as spelled in the source, so `1.50` doesn't turn into `1.5`
+    fn source_token(&mut self) {
+        let lexeme = self
+            .tokens
+            .get(self.current)
+            .map(|token| token.lexeme.clone())
+            .unwrap_or_default();
+        self.token(&lexeme);
+    }
+
+    fn comments_before(&mut self, offset: usize) {
+        while let Some(comment) = self
+            .comments
+            .get(self.comment)
+            .filter(|comment| comment.offset < offset)
+            .cloned()
+        {
+            self.comment += 1;
+            if self.at_line_start {
+                self.blank_line(comment.line);
+            } else {
+                // the comment ends the line, whatever follows moves down
+                self.space();
+                self.continuation = true;
+            }
+            self.write(&comment.lexeme);
+            self.last_line = comment.line;
+            self.line_break();
+        }
+    }
+
+    // ends a statement's line, taking along a comment that trails it
+    fn newline(&mut self) {
+        if self.at_line_start {
+            self.continuation = false;
+            return;
+        }
+        if let Some(comment) = self.comments.get(self.comment).cloned() {
+            if comment.line == self.last_line && comment.offset < self.next_offset() {
+                self.comment += 1;
+                self.space();
+                self.write(&comment.lexeme);
+            }
+        }
+        self.line_break();
+        self.continuation = false;
+    }
+
+    // lines before the closing brace stay inside the block
+    fn block_end(&mut self) {
+        let offset = self.next_offset();
+        self.comments_before(offset);
+        self.indent -= 1;
+        self.token("}");
+    }
+
+    fn has_comments_before_next_token(&self) -> bool {
+        self.comments
+            .get(self.comment)
+            .is_some_and(|comment| comment.offset < self.next_offset())
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) {
+        self.token("{");
+        if stmts.is_empty() && !self.has_comments_before_next_token() {
+            self.token("}");
+            return;
+        }
+        self.newline();
+        self.indent += 1;
+        for stmt in stmts {
+            self.statement(stmt);
+            self.newline();
+        }
+        self.block_end();
+    }
+
+    // bodies of if, while and for stay on the same line as their header
+    fn body(&mut self, stmt: &Stmt) {
+        self.space();
+        self.statement(stmt);
+    }
+
+    fn function(&mut self, function: &Function) {
+        self.token(&function.name.lexeme);
+        self.token("(");
+        for (i, param) in function.params.iter().enumerate() {
+            if i > 0 {
+                self.token(",");
+                self.space();
+            }
+            self.token(&param.lexeme);
+        }
+        self.token(")");
+        self.space();
+        self.block(&function.body);
+    }
+
+    fn binary(&mut self, left: &Expr, operator: &Token, right: &Expr) {
+        self.expression(left);
+        self.space();
+        self.token(&operator.lexeme);
+        self.space();
+        self.expression(right);
+    }
+}
+
+impl ExprVisitor<()> for Formatter {
+    fn visit_binary(&mut self, expr: &crate::expr::Binary) {
+        self.binary(&expr.left, &expr.token, &expr.right);
+    }
+
+    fn visit_grouping(&mut self, expr: &crate::expr::Grouping) {
+        self.token("(");
+        self.expression(&expr.expression);
+        self.token(")");
+    }
+
+    fn visit_unary(&mut self, expr: &crate::expr::Unary) {
+        self.token(&expr.token.lexeme);
+        // `- -1` would otherwise come out as `--1`
+        if let Expr::Unary(right) = &expr.right {
+            if expr.token.lexeme == "-" && right.token.lexeme == "-" {
+                self.space();
+            }
+        }
+        self.expression(&expr.right);
+    }
+
+    fn visit_literal(&mut self, _expr: &crate::expr::Literal) {
+        self.source_token();
+    }
+
+    fn visit_variable(&mut self, expr: &crate::expr::Variable) {
+        self.token(&expr.name.lexeme);
+    }
+
+    fn visit_assign(&mut self, expr: &crate::expr::Assign) {
+        self.token(&expr.name.lexeme);
+        self.space();
+        self.token("=");
+        self.space();
+        self.expression(&expr.value);
+    }
+
+    fn visit_logical(&mut self, expr: &crate::expr::Logical) {
+        self.binary(&expr.left, &expr.operator, &expr.right);
+    }
+
+    fn visit_call(&mut self, expr: &crate::expr::Call) {
+        self.expression(&expr.callee);
+        self.token("(");
+        for (i, argument) in expr.arguments.iter().enumerate() {
+            if i > 0 {
+                self.token(",");
+                self.space();
+            }
+            self.expression(argument);
+        }
+        self.token(")");
+    }
+
+    fn visit_get(&mut self, expr: &crate::expr::Get) {
+        self.expression(&expr.object);
+        self.token(".");
+        self.token(&expr.name.lexeme);
+    }
+
+    fn visit_set(&mut self, expr: &crate::expr::Set) {
+        self.expression(&expr.object);
+        self.token(".");
+        self.token(&expr.name.lexeme);
+        self.space();
+        self.token("=");
+        self.space();
+        self.expression(&expr.value);
+    }
+
+    fn visit_this(&mut self, _expr: &crate::expr::This) {
+        self.token("this");
+    }
+
+    fn visit_super(&mut self, expr: &crate::expr::Super) {
+        self.token("super");
+        self.token(".");
+        self.token(&expr.method.lexeme);
+    }
+
+    fn visit_list(&mut self, expr: &crate::expr::List) {
+        self.token("[");
+        for (i, element) in expr.elements.iter().enumerate() {
+            if i > 0 {
+                self.token(",");
+                self.space();
+            }
+            self.expression(element);
+        }
+        self.token("]");
+    }
+
+    fn visit_index(&mut self, expr: &crate::expr::Index) {
+        self.expression(&expr.object);
+        self.token("[");
+        self.expression(&expr.index);
+        self.token("]");
+    }
+
+    fn visit_set_index(&mut self, expr: &crate::expr::SetIndex) {
+        self.expression(&expr.object);
+        self.token("[");
+        self.expression(&expr.index);
+        self.token("]");
+        self.space();
+        self.token("=");
+        self.space();
+        self.expression(&expr.value);
+    }
+}
+
+impl StmtVisitor<()> for Formatter {
+    fn visit_print(&mut self, stmt: &crate::stmt::Print) {
+        self.token("print");
+        self.space();
+        self.expression(&stmt.expression);
+        self.token(";");
+    }
+
+    fn visit_expression(&mut self, stmt: &crate::stmt::Expression) {
+        self.expression(&stmt.expression);
+        self.token(";");
+    }
+
+    fn visit_var(&mut self, stmt: &crate::stmt::Var) {
+        self.token("var");
+        self.space();
+        self.token(&stmt.token.lexeme);
+        if let Some(initializer) = &stmt.initializer {
+            self.space();
+            self.token("=");
+            self.space();
+            self.expression(initializer);
+        }
+        self.token(";");
+    }
+
+    fn visit_block(&mut self, stmt: &crate::stmt::Block) {
+        self.block(&stmt.statements);
+    }
+
+    fn visit_if(&mut self, stmt: &crate::stmt::If) {
+        self.token("if");
+        self.space();
+        self.token("(");
+        self.expression(&stmt.condition);
+        self.token(")");
+        self.body(&stmt.then_branch);
+
+        if let Some(else_branch) = &stmt.else_branch {
+            // `} else {` when the branch was a block, its own line otherwise
+            if matches!(stmt.then_branch, Stmt::Block(_)) {
+                self.space();
+            } else {
+                self.newline();
+            }
+            self.token("else");
+            self.body(else_branch);
+        }
+    }
+
+    fn visit_while(&mut self, stmt: &crate::stmt::While) {
+        self.token("while");
+        self.space();
+        self.token("(");
+        self.expression(&stmt.condition);
+        self.token(")");
+        self.body(&stmt.body);
+    }
+
+    fn visit_for(&mut self, stmt: &crate::stmt::For) {
+        self.token("for");
+        self.space();
+        self.token("(");
+        match &stmt.initializer {
+            Some(initializer) => self.statement(initializer),
+            None => self.token(";"),
+        }
+        if let Some(condition) = &stmt.condition {
+            self.space();
+            self.expression(condition);
+        }
+        self.token(";");
+        if let Some(increment) = &stmt.increment {
+            self.space();
+            self.expression(increment);
+        }
+        self.token(")");
+        self.body(&stmt.body);
+    }
+
+    fn visit_function(&mut self, stmt: &Rc<Function>) {
+        self.token("fun");
+        self.space();
+        self.function(stmt);
+    }
+
+    fn visit_return(&mut self, stmt: &crate::stmt::Return) {
+        self.token("return");
+        if let Some(value) = &stmt.value {
+            self.space();
+            self.expression(value);
+        }
+        self.token(";");
+    }
+
+    fn visit_class(&mut self, stmt: &crate::stmt::Class) {
+        self.token("class");
+        self.space();
+        self.token(&stmt.name.lexeme);
+        if let Some(superclass) = &stmt.superclass {
+            self.space();
+            self.token("<");
+            self.space();
+            self.token(&superclass.name.lexeme);
+        }
+        self.space();
+        self.token("{");
+        if stmt.methods.is_empty() && !self.has_comments_before_next_token() {
+            self.token("}");
+            return;
+        }
+        self.newline();
+        self.indent += 1;
+        for method in &stmt.methods {
+            self.function(method);
+            self.newline();
+        }
+        self.block_end();
+    }
+
+    fn visit_break(&mut self, _stmt: &crate::stmt::Break) {
+        self.token("break");
+        self.token(";");
+    }
+
+    fn visit_continue(&mut self, _stmt: &crate::stmt::Continue) {
+        self.token("continue");
+        self.token(";");
+    }
+}
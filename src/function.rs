@@ -0,0 +1,87 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use crate::{
+    class::LoxInstance,
+    environment::Environment,
+    error::{LoxError, Unwind},
+    interpreter::Interpreter,
+    stmt::Function,
+    token_type::Object,
+    traits::LoxCallable,
+};
+
+#[derive(Debug)]
+pub struct LoxFunction {
+    declaration: Rc<Function>,
+    closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub fn new(
+        declaration: Rc<Function>,
+        closure: Rc<RefCell<Environment>>,
+        is_initializer: bool,
+    ) -> Self {
+        Self {
+            declaration,
+            closure,
+            is_initializer,
+        }
+    }
+
+    pub fn bind(&self, instance: Rc<RefCell<LoxInstance>>) -> Self {
+        let mut environment = Environment::from(self.closure.clone());
+        environment.define("this".to_string(), Object::Instance(instance));
+        Self::new(
+            self.declaration.clone(),
+            Rc::new(RefCell::new(environment)),
+            self.is_initializer,
+        )
+    }
+
+    // an initializer always hands back the bound instance
+    fn this(&self) -> Object {
+        self.closure
+            .borrow()
+            .values
+            .get("this")
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<fn {}>", self.declaration.name.lexeme)
+    }
+}
+
+impl LoxCallable for LoxFunction {
+    fn name(&self) -> &str {
+        &self.declaration.name.lexeme
+    }
+
+    fn arity(&self) -> usize {
+        self.declaration.params.len()
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Object>,
+    ) -> Result<Object, LoxError> {
+        let mut environment = Environment::from(self.closure.clone());
+        for (param, arg) in self.declaration.params.iter().zip(arguments) {
+            environment.define(param.lexeme.clone(), arg);
+        }
+
+        match interpreter.execute_block(&self.declaration.body, environment) {
+            Ok(()) | Err(Unwind::Return(_)) if self.is_initializer => Ok(self.this()),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(err)) => Err(err),
+            // loop control can't cross a function boundary, the resolver rejects it
+            Ok(()) | Err(Unwind::Break) | Err(Unwind::Continue) => Ok(Object::Nil),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::{
+    cell::RefCell,
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    error,
+    lox::{Error, Lox},
+};
+
+// golden-file runner for .lox scripts, expectations live in comments the
+// same way as the craftinginterpreters suite:
+//   print 1 + 2;        // expect: 3
+//   nil + 1;            // expect runtime error: Unexpected combination
+//   var a = ;           // Error at ';': Expect expression.
+//   // [line 7] Error at end: Expect ';' after value.
+
+const EXPECT_OUTPUT: &str = "// expect: ";
+const EXPECT_RUNTIME_ERROR: &str = "// expect runtime error: ";
+const EXPECT_ERROR: &str = "// Error";
+const EXPECT_LINE_ERROR: &str = "// [line ";
+
+#[derive(Debug, Default)]
+struct Expectations {
+    output: Vec<(usize, String)>,
+    errors: Vec<String>,
+    runtime_error: Option<(usize, String)>,
+}
+
+impl Expectations {
+    fn parse(source: &str) -> Self {
+        let mut expectations = Self::default();
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+            if let Some(pos) = text.find(EXPECT_OUTPUT) {
+                let output = &text[pos + EXPECT_OUTPUT.len()..];
+                expectations.output.push((line, output.to_string()));
+            } else if let Some(pos) = text.find(EXPECT_RUNTIME_ERROR) {
+                let msg = &text[pos + EXPECT_RUNTIME_ERROR.len()..];
+                expectations.runtime_error = Some((line, msg.to_string()));
+            } else if let Some(pos) = text.find(EXPECT_LINE_ERROR) {
+                // the line is spelled out, used for errors reported elsewhere
+                expectations.errors.push(text[pos + 3..].to_string());
+            } else if let Some(pos) = text.find(EXPECT_ERROR) {
+                let error = &text[pos + 3..];
+                expectations.errors.push(format!("[line {line}] {error}"));
+            }
+        }
+        expectations
+    }
+}
+
+// print output collected in memory so it can be compared
+#[derive(Debug, Clone, Default)]
+struct Capture(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Capture {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.borrow()).into_owned()
+    }
+}
+
+#[derive(Debug)]
+pub struct TestResult {
+    pub path: PathBuf,
+    pub failures: Vec<String>,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub results: Vec<TestResult>,
+}
+
+impl Report {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|result| result.passed()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in self.results.iter().filter(|result| !result.passed()) {
+            writeln!(f, "FAIL {}", result.path.display())?;
+            for failure in &result.failures {
+                writeln!(f, "    {failure}")?;
+            }
+        }
+        write!(f, "{} passed, {} failed", self.passed(), self.failed())
+    }
+}
+
+pub fn run_file<P: AsRef<Path>>(path: P) -> io::Result<TestResult> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)?;
+    Ok(TestResult {
+        path: path.to_path_buf(),
+        failures: check(&source),
+    })
+}
+
+// runs every .lox file under dir, nested directories included
+pub fn run_dir<P: AsRef<Path>>(dir: P) -> io::Result<Report> {
+    let mut paths = Vec::new();
+    collect(dir.as_ref(), &mut paths)?;
+    paths.sort();
+
+    let mut report = Report::default();
+    for path in paths {
+        report.results.push(run_file(path)?);
+    }
+    Ok(report)
+}
+
+fn collect(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect(&path, paths)?;
+        } else if path.extension().is_some_and(|ext| ext == "lox") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn check(source: &str) -> Vec<String> {
+    let expected = Expectations::parse(source);
+    let capture = Capture::default();
+    let mut lox = Lox::new();
+    lox.set_output(capture.clone());
+
+    let mut failures = Vec::new();
+    let mut errors = Vec::new();
+    let mut runtime_error = None;
+    match lox.eval(source) {
+        Ok(_) => {}
+        Err(Error::Script(diagnostics))
+            if matches!(diagnostics.errors().first(), Some(error::Error::Runtime(_))) =>
+        {
+            runtime_error = diagnostics
+                .errors()
+                .first()
+                .map(|err| (err.inner().line, err.inner().msg.clone()));
+        }
+        Err(Error::Script(diagnostics)) => {
+            errors = diagnostics
+                .errors()
+                .iter()
+                .map(|err| err.to_string())
+                .collect();
+        }
+        Err(err) => failures.push(format!("unexpected failure: {err}")),
+    }
+
+    for error in &expected.errors {
+        if !errors.contains(error) {
+            failures.push(format!("missing expected error: {error}"));
+        }
+    }
+    for error in &errors {
+        if !expected.errors.contains(error) {
+            failures.push(format!("unexpected error: {error}"));
+        }
+    }
+
+    match (&expected.runtime_error, &runtime_error) {
+        (Some((line, msg)), None) => {
+            failures.push(format!("expected runtime error on line {line}: {msg}"))
+        }
+        (None, Some((line, msg))) => {
+            failures.push(format!("unexpected runtime error on line {line}: {msg}"))
+        }
+        (Some(expected), Some(actual)) if expected != actual => failures.push(format!(
+            "expected runtime error on line {}: {}, got line {}: {}",
+            expected.0, expected.1, actual.0, actual.1
+        )),
+        _ => {}
+    }
+
+    let output = capture.contents();
+    let actual = output.lines().collect::<Vec<&str>>();
+    for (index, (line, expected)) in expected.output.iter().enumerate() {
+        match actual.get(index) {
+            Some(got) if got == expected => {}
+            Some(got) => failures.push(format!(
+                "expected output '{expected}' on line {line}, got '{got}'"
+            )),
+            None => failures.push(format!(
+                "missing expected output '{expected}' on line {line}"
+            )),
+        }
+    }
+    for got in actual.iter().skip(expected.output.len()) {
+        failures.push(format!("unexpected output '{got}'"));
+    }
+
+    failures
+}
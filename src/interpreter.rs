@@ -1,66 +1,243 @@
-use std::borrow::Borrow;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    io::{self, Write},
+    rc::Rc,
+};
 
 use crate::{
+    class::{LoxClass, LoxInstance},
     environment::Environment,
-    error::LoxError,
+    error::{LoxError, StackFrame, Unwind},
     expr::Expr,
+    function::LoxFunction,
+    lox_error,
+    native::{self, NativeFunction},
     stmt::Stmt,
+    token::Token,
     token_type::{Object, TokenType},
-    traits::{ExprVisitor, StmtVisitor},
+    traits::{ExprVisitor, LoxCallable, StmtVisitor},
 };
 
+// native stack a script's calls may use before they fail with a runtime
+// error instead of overflowing it. half of the 2 MiB a spawned thread gets
+pub const DEFAULT_STACK_LIMIT: usize = 1024 * 1024;
+
+// address of a local in the caller's frame, the stack grows down from here
+#[inline(never)]
+fn stack_position() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
 #[derive(Debug)]
+pub struct CallFrame {
+    pub function: String,
+    pub call_site: Token,
+}
+
 pub struct Interpreter {
-    environment: Environment,
+    pub(crate) globals: Rc<RefCell<Environment>>,
+    pub(crate) environment: Rc<RefCell<Environment>>,
+    // expression id -> number of scopes between use and binding
+    locals: HashMap<usize, usize>,
+    call_stack: Vec<CallFrame>,
+    // bytes of native stack calls may take, measured from where interpret
+    // was entered
+    stack_limit: usize,
+    stack_base: usize,
+    // where print writes, stdout unless the host swaps it out
+    output: Box<dyn Write>,
+}
+
+impl fmt::Debug for Interpreter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interpreter")
+            .field("globals", &self.globals)
+            .field("environment", &self.environment)
+            .field("locals", &self.locals)
+            .field("call_stack", &self.call_stack)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Default for Interpreter {
     fn default() -> Self {
-        Self {
-            environment: Environment::initialize(),
-        }
+        let globals = Rc::new(RefCell::new(Environment::initialize()));
+        let mut interpreter = Self {
+            globals: globals.clone(),
+            environment: globals,
+            locals: HashMap::new(),
+            call_stack: Vec::new(),
+            stack_limit: DEFAULT_STACK_LIMIT,
+            stack_base: 0,
+            output: Box::new(io::stdout()),
+        };
+        interpreter.define_native("clock", 0, native::clock);
+        interpreter.define_native("len", 1, native::len);
+        interpreter.define_native("push", 2, native::push);
+        interpreter.define_native("pop", 1, native::pop);
+        interpreter
     }
 }
 
 impl Interpreter {
+    pub fn define_native<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[Object]) -> Result<Object, LoxError> + 'static,
+    {
+        let native = NativeFunction::new(name, arity, function);
+        self.globals
+            .borrow_mut()
+            .define(name.to_string(), Object::Native(Rc::new(native)));
+    }
+
+    // back to a fresh session, print keeps its sink
+    pub fn reset(&mut self) {
+        let output = std::mem::replace(&mut self.output, Box::new(io::sink()));
+        let stack_limit = self.stack_limit;
+        *self = Self::default();
+        self.output = output;
+        self.stack_limit = stack_limit;
+    }
+
+    // hosts running scripts on a thread with a bigger stack can allow deeper
+    // recursion, keep some room below the thread's size for the host itself
+    pub fn set_stack_limit(&mut self, bytes: usize) {
+        self.stack_limit = bytes;
+    }
+
+    fn list(object: Object, bracket: &Token) -> Result<Rc<RefCell<Vec<Object>>>, LoxError> {
+        match object {
+            Object::List(list) => Ok(list),
+            _ => Err(lox_error!(bracket.clone(), "Only lists can be indexed.")),
+        }
+    }
+
+    fn list_index(list: &[Object], index: &Object, bracket: &Token) -> Result<usize, LoxError> {
+        let index = match index {
+            Object::Number(n) if n.fract() == 0.0 => *n,
+            _ => {
+                return Err(lox_error!(
+                    bracket.clone(),
+                    "List index must be an integer."
+                ))
+            }
+        };
+        if index < 0.0 || index >= list.len() as f64 {
+            return Err(lox_error!(
+                bracket.clone(),
+                format!(
+                    "Index {} out of range for list of length {}.",
+                    index,
+                    list.len()
+                )
+                .as_str()
+            ));
+        }
+        Ok(index as usize)
+    }
+
+    pub fn set_output<W: Write + 'static>(&mut self, output: W) {
+        self.output = Box::new(output);
+    }
+
     fn evaluate(&mut self, expr: &Expr) -> Result<Object, LoxError> {
         expr.accept(self)
     }
 
-    fn execute(&mut self, stmt: &Stmt) -> Result<(), LoxError> {
+    fn execute(&mut self, stmt: &Stmt) -> Result<(), Unwind> {
         stmt.accept(self)
     }
 
-    // pub fn interpret(&mut self, expr: &Expr) -> Result<(), LoxError> {
-    //     let value = self.evaluate(expr);
-    //     match value {
-    //         Ok(val) => {
-    //             println!("{}", val);
-    //             Ok(())
-    //         }
-    //         Err(err) => Err(err),
-    //     }
-    // }
-    pub fn interpret(&mut self, stmts: Vec<Stmt>) -> Result<(), LoxError> {
+    pub fn resolve(&mut self, id: usize, depth: usize) {
+        self.locals.insert(id, depth);
+    }
+
+    fn look_up_variable(&self, id: usize, name: &Token) -> Result<Object, LoxError> {
+        match self.locals.get(&id) {
+            Some(distance) => self.environment.borrow().get_at(*distance, name),
+            None => self.globals.borrow().get(name),
+        }
+    }
+
+    // yields the value of a trailing expression statement, nil otherwise
+    pub fn interpret(&mut self, stmts: Vec<Stmt>) -> Result<Object, LoxError> {
+        self.stack_base = stack_position();
+        let mut value = Object::Nil;
         for stmt in stmts {
-            // println!("{:?}", stmt);
-            self.execute(stmt.borrow())?;
-            // println!("{:#?}", self.environment);
+            let result = match &stmt {
+                Stmt::Expression(expr) => self
+                    .evaluate(&expr.expression)
+                    .map(|val| value = val)
+                    .map_err(Unwind::Error),
+                _ => {
+                    value = Object::Nil;
+                    self.execute(&stmt)
+                }
+            };
+            match result {
+                Ok(()) => {}
+                Err(Unwind::Error(mut err)) => {
+                    if err.trace.is_empty() {
+                        err.trace = self.stack_trace(&err).into();
+                    }
+                    return Err(err);
+                }
+                Err(_) => break,
+            }
         }
-        Ok(())
+        Ok(value)
     }
 
-    fn execute_block(
+    // walks the live call stack, the innermost frame stopped where err points
+    fn stack_trace(&self, err: &LoxError) -> Vec<StackFrame> {
+        let mut trace = Vec::new();
+        let mut line = err.line;
+        let mut source = err.span.map_or(0, |span| span.source);
+        for frame in self.call_stack.iter().rev() {
+            trace.push(StackFrame::new(&frame.function, line, source));
+            line = frame.call_site.line;
+            source = frame.call_site.source;
+        }
+        trace.push(StackFrame::new("<script>", line, source));
+        trace
+    }
+
+    pub(crate) fn execute_block(
         &mut self,
         statements: &[Stmt],
         environment: Environment,
-    ) -> Result<(), LoxError> {
-        let outer_scope = self.environment.clone();
-        self.environment = environment;
-        for statement in statements {
-            self.execute(statement)?;
-        }
+    ) -> Result<(), Unwind> {
+        let outer_scope =
+            std::mem::replace(&mut self.environment, Rc::new(RefCell::new(environment)));
+        let result = statements
+            .iter()
+            .try_for_each(|statement| self.execute(statement));
         self.environment = outer_scope;
+        result
+    }
+
+    fn execute_for(&mut self, stmt: &crate::stmt::For) -> Result<(), Unwind> {
+        if let Some(initializer) = &stmt.initializer {
+            self.execute(initializer)?;
+        }
+        loop {
+            if let Some(condition) = &stmt.condition {
+                if !self.evaluate(condition)?.is_truthy() {
+                    break;
+                }
+            }
+            match self.execute(&stmt.body) {
+                Ok(()) | Err(Unwind::Continue) => {}
+                Err(Unwind::Break) => break,
+                Err(unwind) => return Err(unwind),
+            }
+            if let Some(increment) = &stmt.increment {
+                self.evaluate(increment)?;
+            }
+        }
         Ok(())
     }
 }
@@ -82,10 +259,7 @@ impl ExprVisitor<Result<Object, LoxError>> for Interpreter {
         match expr.token.token_type {
             TokenType::Minus => Ok(right.negate_wrap()),
             TokenType::Bang => Ok(right.truth_wrap()),
-            _ => Err(LoxError::from(
-                expr.token.borrow().to_owned(),
-                "Unreachable",
-            )),
+            _ => Err(LoxError::from(expr.token.clone(), "Unreachable")),
         }
     }
 
@@ -94,13 +268,19 @@ impl ExprVisitor<Result<Object, LoxError>> for Interpreter {
     }
 
     fn visit_variable(&mut self, expr: &crate::expr::Variable) -> Result<Object, LoxError> {
-        let var = self.environment.get(&expr.name)?;
-        Ok(var)
+        self.look_up_variable(expr.id, &expr.name)
     }
 
     fn visit_assign(&mut self, expr: &crate::expr::Assign) -> Result<Object, LoxError> {
         let val = self.evaluate(&expr.value)?;
-        self.environment.assign(&expr.name, val.clone())?;
+        match self.locals.get(&expr.id) {
+            Some(distance) => {
+                self.environment
+                    .borrow_mut()
+                    .assign_at(*distance, &expr.name, val.clone())?;
+            }
+            None => self.globals.borrow_mut().assign(&expr.name, val.clone())?,
+        }
         Ok(val)
     }
 
@@ -117,34 +297,180 @@ impl ExprVisitor<Result<Object, LoxError>> for Interpreter {
 
         self.evaluate(&expr.right)
     }
+
+    fn visit_call(&mut self, expr: &crate::expr::Call) -> Result<Object, LoxError> {
+        let callee = self.evaluate(&expr.callee)?;
+
+        let mut arguments = Vec::<Object>::new();
+        for argument in expr.arguments.iter() {
+            arguments.push(self.evaluate(argument)?);
+        }
+
+        let function: &dyn LoxCallable = match &callee {
+            Object::Function(fun) => fun.as_ref(),
+            Object::Class(class) => class,
+            Object::Native(native) => native.as_ref(),
+            _ => {
+                return Err(lox_error!(
+                    expr.paren.clone(),
+                    "Can only call functions and classes."
+                ))
+            }
+        };
+
+        if arguments.len() != function.arity() {
+            return Err(lox_error!(
+                expr.paren.clone(),
+                format!(
+                    "Expected {} arguments but got {}.",
+                    function.arity(),
+                    arguments.len()
+                )
+                .as_str()
+            ));
+        }
+
+        if self.stack_base.saturating_sub(stack_position()) > self.stack_limit {
+            return Err(lox_error!(expr.paren.clone(), "Stack overflow."));
+        }
+        self.call_stack.push(CallFrame {
+            function: function.name().to_string(),
+            call_site: expr.paren.clone(),
+        });
+        let result = function.call(self, arguments).map_err(|mut err| {
+            // natives don't know where they were called from
+            if err.span.is_none() {
+                err = lox_error!(expr.paren.clone(), &err.msg);
+            }
+            if err.trace.is_empty() {
+                err.trace = self.stack_trace(&err).into();
+            }
+            err
+        });
+        self.call_stack.pop();
+        result
+    }
+
+    fn visit_get(&mut self, expr: &crate::expr::Get) -> Result<Object, LoxError> {
+        match self.evaluate(&expr.object)? {
+            Object::Instance(instance) => LoxInstance::get(&instance, &expr.name),
+            _ => Err(lox_error!(
+                expr.name.clone(),
+                "Only instances have properties."
+            )),
+        }
+    }
+
+    fn visit_set(&mut self, expr: &crate::expr::Set) -> Result<Object, LoxError> {
+        let instance = match self.evaluate(&expr.object)? {
+            Object::Instance(instance) => instance,
+            _ => return Err(lox_error!(expr.name.clone(), "Only instances have fields.")),
+        };
+
+        let value = self.evaluate(&expr.value)?;
+        instance.borrow_mut().set(&expr.name, value.clone());
+        Ok(value)
+    }
+
+    fn visit_this(&mut self, expr: &crate::expr::This) -> Result<Object, LoxError> {
+        self.look_up_variable(expr.id, &expr.keyword)
+    }
+
+    fn visit_list(&mut self, expr: &crate::expr::List) -> Result<Object, LoxError> {
+        let mut elements = Vec::<Object>::new();
+        for element in expr.elements.iter() {
+            elements.push(self.evaluate(element)?);
+        }
+        Ok(Object::List(Rc::new(RefCell::new(elements))))
+    }
+
+    fn visit_index(&mut self, expr: &crate::expr::Index) -> Result<Object, LoxError> {
+        let list = Self::list(self.evaluate(&expr.object)?, &expr.bracket)?;
+        let index = self.evaluate(&expr.index)?;
+        let list = list.borrow();
+        let index = Self::list_index(&list, &index, &expr.bracket)?;
+        Ok(list[index].clone())
+    }
+
+    fn visit_set_index(&mut self, expr: &crate::expr::SetIndex) -> Result<Object, LoxError> {
+        let list = Self::list(self.evaluate(&expr.object)?, &expr.bracket)?;
+        let index = self.evaluate(&expr.index)?;
+        let value = self.evaluate(&expr.value)?;
+        let mut list = list.borrow_mut();
+        let index = Self::list_index(&list, &index, &expr.bracket)?;
+        list[index] = value.clone();
+        Ok(value)
+    }
+
+    fn visit_super(&mut self, expr: &crate::expr::Super) -> Result<Object, LoxError> {
+        let distance = self.locals.get(&expr.id).copied().unwrap_or_default();
+        let superclass = match self.environment.borrow().get_at(distance, &expr.keyword)? {
+            Object::Class(class) => class,
+            _ => {
+                return Err(lox_error!(
+                    expr.keyword.clone(),
+                    "Superclass must be a class."
+                ))
+            }
+        };
+
+        // "this" is always bound one scope inside "super"
+        let this = Token {
+            token_type: TokenType::This,
+            lexeme: "this".to_string(),
+            ..expr.keyword.clone()
+        };
+        let instance = match self.environment.borrow().get_at(distance - 1, &this)? {
+            Object::Instance(instance) => instance,
+            _ => return Err(lox_error!(this, "Only instances have superclasses.")),
+        };
+
+        match superclass.find_method(&expr.method.lexeme) {
+            Some(method) => Ok(Object::Function(Rc::new(method.bind(instance)))),
+            None => Err(lox_error!(
+                expr.method.clone(),
+                format!("Undefined property '{}'.", expr.method.lexeme).as_str()
+            )),
+        }
+    }
 }
 
-impl StmtVisitor<Result<(), LoxError>> for Interpreter {
-    fn visit_print(&mut self, stmt: &crate::stmt::Print) -> Result<(), LoxError> {
+impl StmtVisitor<Result<(), Unwind>> for Interpreter {
+    fn visit_print(&mut self, stmt: &crate::stmt::Print) -> Result<(), Unwind> {
         let value = self.evaluate(&stmt.expression)?;
-        println!("{}", value);
+        writeln!(self.output, "{}", value).map_err(|err| {
+            lox_error!(
+                stmt.keyword.clone(),
+                format!("Failed to print: {err}.").as_str()
+            )
+        })?;
         Ok(())
     }
 
-    fn visit_expression(&mut self, stmt: &crate::stmt::Expression) -> Result<(), LoxError> {
+    fn visit_expression(&mut self, stmt: &crate::stmt::Expression) -> Result<(), Unwind> {
         self.evaluate(&stmt.expression)?;
         Ok(())
     }
 
-    fn visit_var(&mut self, stmt: &crate::stmt::Var) -> Result<(), LoxError> {
-        let val = self.evaluate(&stmt.initializer)?;
-        self.environment.define(stmt.token.lexeme.clone(), val);
+    fn visit_var(&mut self, stmt: &crate::stmt::Var) -> Result<(), Unwind> {
+        let val = match &stmt.initializer {
+            Some(initializer) => self.evaluate(initializer)?,
+            None => Object::Nil,
+        };
+        self.environment
+            .borrow_mut()
+            .define(stmt.token.lexeme.clone(), val);
         // println!("{:#?}", self.environment);
         Ok(())
     }
 
-    fn visit_block(&mut self, stmt: &crate::stmt::Block) -> Result<(), LoxError> {
+    fn visit_block(&mut self, stmt: &crate::stmt::Block) -> Result<(), Unwind> {
         let env = Environment::from(self.environment.clone());
         self.execute_block(&stmt.statements, env)?;
         Ok(())
     }
 
-    fn visit_if(&mut self, stmt: &crate::stmt::If) -> Result<(), LoxError> {
+    fn visit_if(&mut self, stmt: &crate::stmt::If) -> Result<(), Unwind> {
         if self.evaluate(&stmt.condition)?.is_truthy() {
             self.execute(&stmt.then_branch)?;
         } else if stmt.else_branch.is_some() {
@@ -153,10 +479,92 @@ impl StmtVisitor<Result<(), LoxError>> for Interpreter {
         Ok(())
     }
 
-    fn visit_while(&mut self, stmt: &crate::stmt::While) -> Result<(), LoxError> {
+    fn visit_while(&mut self, stmt: &crate::stmt::While) -> Result<(), Unwind> {
         while self.evaluate(&stmt.condition)?.is_truthy() {
-            self.execute(&stmt.body)?;
+            match self.execute(&stmt.body) {
+                Ok(()) | Err(Unwind::Continue) => {}
+                Err(Unwind::Break) => break,
+                Err(unwind) => return Err(unwind),
+            }
         }
         Ok(())
     }
+
+    fn visit_for(&mut self, stmt: &crate::stmt::For) -> Result<(), Unwind> {
+        // the initializer gets a scope of its own, like a block
+        let environment = Environment::from(self.environment.clone());
+        let outer_scope =
+            std::mem::replace(&mut self.environment, Rc::new(RefCell::new(environment)));
+        let result = self.execute_for(stmt);
+        self.environment = outer_scope;
+        result
+    }
+
+    fn visit_function(&mut self, stmt: &Rc<crate::stmt::Function>) -> Result<(), Unwind> {
+        let function = LoxFunction::new(stmt.clone(), self.environment.clone(), false);
+        self.environment.borrow_mut().define(
+            stmt.name.lexeme.clone(),
+            Object::Function(Rc::new(function)),
+        );
+        Ok(())
+    }
+
+    fn visit_return(&mut self, stmt: &crate::stmt::Return) -> Result<(), Unwind> {
+        let value = match &stmt.value {
+            Some(expr) => self.evaluate(expr)?,
+            None => Object::Nil,
+        };
+        Err(Unwind::Return(value))
+    }
+
+    fn visit_class(&mut self, stmt: &crate::stmt::Class) -> Result<(), Unwind> {
+        let superclass = match &stmt.superclass {
+            Some(superclass) => match self.visit_variable(superclass)? {
+                Object::Class(class) => Some(class),
+                _ => {
+                    return Err(Unwind::Error(lox_error!(
+                        superclass.name.clone(),
+                        "Superclass must be a class."
+                    )))
+                }
+            },
+            None => None,
+        };
+
+        self.environment
+            .borrow_mut()
+            .define(stmt.name.lexeme.clone(), Object::Nil);
+
+        let enclosing = self.environment.clone();
+        if let Some(superclass) = &superclass {
+            let mut environment = Environment::from(enclosing.clone());
+            environment.define("super".to_string(), Object::Class(superclass.clone()));
+            self.environment = Rc::new(RefCell::new(environment));
+        }
+
+        let mut methods = HashMap::new();
+        for method in stmt.methods.iter() {
+            let function = LoxFunction::new(
+                method.clone(),
+                self.environment.clone(),
+                method.name.lexeme == "init",
+            );
+            methods.insert(method.name.lexeme.clone(), Rc::new(function));
+        }
+
+        let class = LoxClass::new(stmt.name.lexeme.clone(), superclass, methods);
+        self.environment = enclosing;
+        self.environment
+            .borrow_mut()
+            .assign(&stmt.name, Object::Class(Rc::new(class)))?;
+        Ok(())
+    }
+
+    fn visit_break(&mut self, _stmt: &crate::stmt::Break) -> Result<(), Unwind> {
+        Err(Unwind::Break)
+    }
+
+    fn visit_continue(&mut self, _stmt: &crate::stmt::Continue) -> Result<(), Unwind> {
+        Err(Unwind::Continue)
+    }
 }
@@ -0,0 +1,27 @@
+pub mod ast_printer;
+pub mod class;
+pub mod diagnostics;
+pub mod environment;
+pub mod error;
+pub mod expr;
+pub mod formatter;
+pub mod function;
+pub mod harness;
+pub mod interpreter;
+pub mod lox;
+pub mod macros;
+pub mod native;
+pub mod parser;
+pub mod resolver;
+pub mod scanner;
+pub mod stmt;
+pub mod token;
+pub mod token_type;
+pub mod traits;
+
+pub use ast_printer::AstPrinter;
+pub use diagnostics::Diagnostics;
+pub use error::LoxError;
+pub use formatter::Formatter;
+pub use lox::{Error, Lox};
+pub use token_type::Object;
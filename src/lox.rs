@@ -0,0 +1,206 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{
+    diagnostics::Diagnostics,
+    error::{self, LoxError},
+    interpreter::Interpreter,
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+    stmt::Stmt,
+    token,
+    token_type::Object,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Script(Diagnostics),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Script(diagnostics) => write!(f, "{diagnostics}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<Diagnostics> for Error {
+    fn from(diagnostics: Diagnostics) -> Self {
+        Self::Script(diagnostics)
+    }
+}
+
+impl Error {
+    // 66 is EX_NOINPUT, scripts use their stage's code
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Io(_) => 66,
+            Self::Script(diagnostics) => diagnostics.exit_code(),
+        }
+    }
+}
+
+// an interpreter session, globals persist across calls to eval
+#[derive(Debug, Default)]
+pub struct Lox {
+    interpreter: Interpreter,
+    // source id -> name, so traces can point into earlier sources
+    scripts: HashMap<u64, String>,
+}
+
+impl Lox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn eval(&mut self, source: &str) -> Result<Object, Error> {
+        let stmts = Self::parse(source)?;
+        self.execute(stmts)
+    }
+
+    // like eval, but a trailing expression may leave off its semicolon
+    pub fn eval_line(&mut self, source: &str) -> Result<Object, Error> {
+        let stmts = Self::parse_line(source)?;
+        self.execute(stmts)
+    }
+
+    // the name traces give frames running code from this source, e.g. a file
+    // loaded into a repl session
+    pub fn name_source(&mut self, source: &str, name: &str) {
+        self.scripts
+            .insert(token::source_id(source), name.to_string());
+    }
+
+    // repl counterpart of parse
+    pub fn parse_line(source: &str) -> Result<Vec<Stmt>, Error> {
+        parse_scanned(&mut Scanner::new(source), true)
+    }
+
+    fn execute(&mut self, stmts: Vec<Stmt>) -> Result<Object, Error> {
+        if let Err(errs) = Resolver::new(&mut self.interpreter).resolve(&stmts) {
+            let mut diagnostics = Diagnostics::new();
+            diagnostics.extend(errs);
+            return Err(Error::Script(diagnostics));
+        }
+        let value = self.interpreter.interpret(stmts).map_err(|mut err| {
+            for frame in err.trace.iter_mut() {
+                frame.script = self.scripts.get(&frame.source).cloned();
+            }
+            Diagnostics::from(error::Error::Runtime(err))
+        })?;
+        Ok(value)
+    }
+
+    // scan and parse only, nothing is resolved or run
+    pub fn parse(source: &str) -> Result<Vec<Stmt>, Error> {
+        parse_scanned(&mut Scanner::new(source), false)
+    }
+
+    // false while more input could still finish the source, such as an open
+    // block or string, so the repl knows to keep reading lines. only the
+    // first error counts, later ones may just be fallout from recovery
+    pub fn is_complete(source: &str) -> bool {
+        match Self::parse_line(source) {
+            Err(Error::Script(diagnostics)) => !diagnostics
+                .errors()
+                .first()
+                .is_some_and(|err| err.inner().at_end),
+            _ => true,
+        }
+    }
+
+    pub fn run_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Object, Error> {
+        let source = fs::read_to_string(&path)?;
+        self.name_source(&source, &path.as_ref().display().to_string());
+        self.eval(&source)
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Object> {
+        self.interpreter.globals.borrow().values.get(name).cloned()
+    }
+
+    // sorted by name
+    pub fn globals(&self) -> Vec<(String, Object)> {
+        let mut globals = self
+            .interpreter
+            .globals
+            .borrow()
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<(String, Object)>>();
+        globals.sort_by(|a, b| a.0.cmp(&b.0));
+        globals
+    }
+
+    pub fn set_global(&mut self, name: &str, value: Object) {
+        self.interpreter
+            .globals
+            .borrow_mut()
+            .define(name.to_string(), value);
+    }
+
+    pub fn define_native<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[Object]) -> Result<Object, LoxError> + 'static,
+    {
+        self.interpreter.define_native(name, arity, function);
+    }
+
+    // drops every global and native the host defined, output stays where it is
+    pub fn reset(&mut self) {
+        self.interpreter.reset();
+    }
+
+    // redirect print, e.g. into a buffer to capture a script's output
+    pub fn set_output<W: Write + 'static>(&mut self, output: W) {
+        self.interpreter.set_output(output);
+    }
+
+    // bytes of native stack nested calls may use before they fail with a
+    // runtime error. the default fits a thread with the usual 2 MiB
+    pub fn set_stack_limit(&mut self, bytes: usize) {
+        self.interpreter.set_stack_limit(bytes);
+    }
+
+    pub fn interpreter(&mut self) -> &mut Interpreter {
+        &mut self.interpreter
+    }
+}
+
+// the scanner is passed in so callers can configure it and keep its tokens
+pub(crate) fn parse_scanned(scanner: &mut Scanner, repl: bool) -> Result<Vec<Stmt>, Error> {
+    let mut diagnostics = Diagnostics::new();
+    if let Err(errs) = scanner.scan_tokens() {
+        diagnostics.extend(errs);
+    }
+
+    let mut parser = Parser::new(scanner.tokens.clone());
+    if repl {
+        parser = parser.repl_mode();
+    }
+    match parser.parse() {
+        Ok(stmts) if !diagnostics.has_errors() => Ok(stmts),
+        Ok(_) => Err(Error::Script(diagnostics)),
+        Err(errs) => {
+            diagnostics.extend(errs);
+            Err(Error::Script(diagnostics))
+        }
+    }
+}
@@ -1,106 +1,306 @@
-pub mod environment;
-pub mod error;
-pub mod expr;
-pub mod interpreter;
-pub mod macros;
-pub mod parser;
-pub mod scanner;
-pub mod stmt;
-pub mod token;
-pub mod token_type;
-pub mod traits;
-
-use std::{
-    env,
-    fs::File,
-    io::{self, Read, Write},
-    process,
-    sync::{Arc, Mutex},
-};
-
-use interpreter::Interpreter;
-use lazy_static::lazy_static;
-use parser::Parser;
-use scanner::Scanner;
-
-use crate::error::LoxError;
-
-lazy_static! {
-    pub static ref ERROR: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
-}
+use std::{env, fs, path::PathBuf, process, thread};
+
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+use lox_ast::{harness, scanner::Scanner, AstPrinter, Diagnostics, Error, Formatter, Lox, Object};
+
+// the tree-walker recurses on the native stack, scripts run on a thread
+// with plenty of it and may use all but a few MiB for nested calls
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+const STACK_LIMIT: usize = STACK_SIZE - 8 * 1024 * 1024;
 
 fn main() {
-    let mut args = env::args();
-    let mut interpreter = Interpreter::default();
-    match args.len() {
-        0..=1 => {
-            run_prompt(&mut interpreter);
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run_main)
+        .unwrap_or_else(|err| {
+            eprintln!("{err}");
+            process::exit(71);
+        });
+    if interpreter.join().is_err() {
+        process::exit(101);
+    }
+}
+
+fn run_main() {
+    let args = env::args().collect::<Vec<String>>();
+    let mut lox = Lox::new();
+    lox.set_stack_limit(STACK_LIMIT);
+    match args.as_slice() {
+        [] | [_] => {
+            run_prompt(&mut lox);
+        }
+        [_, command, rest @ ..] if command == "fmt" => {
+            run_fmt(rest);
+        }
+        [_, command, dir] if command == "test" => {
+            run_tests(dir);
+        }
+        [_, flag, path] if flag == "--dump-ast" => {
+            dump_ast(path);
         }
-        2 => {
-            run_file(args.nth(1).unwrap_or_default(), &mut interpreter);
+        [_, flag, path] if flag == "--dump-tokens" && path != "--json" => {
+            dump_tokens(path, false);
         }
-        _ => {
-            println!("Usage: jlox [script]");
-            process::exit(64);
+        [_, flag, json, path] if flag == "--dump-tokens" && json == "--json" => {
+            dump_tokens(path, true);
         }
+        [_, path] => {
+            run_file(path.to_string(), &mut lox);
+        }
+        _ => usage(),
     }
 }
 
-fn run_prompt(runner: &mut Interpreter) {
+fn usage() -> ! {
+    eprintln!("Usage: jlox [script]");
+    eprintln!("       jlox --dump-ast <script>");
+    eprintln!("       jlox --dump-tokens [--json] <script>");
+    eprintln!("       jlox fmt [--check] <script>...");
+    eprintln!("       jlox test <dir>");
+    process::exit(64);
+}
+
+// lines are gathered until they form complete statements, ctrl-c throws away
+// the pending input and ctrl-d leaves
+fn run_prompt(lox: &mut Lox) {
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(74);
+        }
+    };
+    let history = history_path();
+    if let Some(path) = &history {
+        let _ = editor.load_history(path);
+    }
+
     let mut buffer = String::new();
     loop {
-        buffer.clear();
-        print!("> ");
-        let _ = io::stdout().flush();
-        io::stdin().read_line(&mut buffer).unwrap_or_default();
-        match run(buffer.as_str(), runner) {
-            true => {
-                LoxError::clear();
+        let prompt = if buffer.is_empty() { "> " } else { "... " };
+        match editor.readline(prompt) {
+            Ok(line) if buffer.is_empty() && line.trim_start().starts_with(':') => {
+                let _ = editor.add_history_entry(line.trim());
+                run_command(line.trim(), lox);
+            }
+            Ok(line) => {
+                buffer.push_str(&line);
+                buffer.push('\n');
+                if !Lox::is_complete(&buffer) {
+                    continue;
+                }
+                if !buffer.trim().is_empty() {
+                    let _ = editor.add_history_entry(buffer.trim_end());
+                }
+                run_line(&buffer, lox);
+                buffer.clear();
             }
-            false => {
-                LoxError::clear();
-                continue;
+            Err(ReadlineError::Interrupted) => buffer.clear(),
+            Err(ReadlineError::Eof) => break,
+            Err(err) => {
+                eprintln!("{err}");
+                break;
             }
-        };
+        }
+    }
+
+    if let Some(path) = &history {
+        let _ = editor.save_history(path);
     }
 }
 
-fn run_file(path: String, runner: &mut Interpreter) {
+const HELP: &str = ":env           list global variables
+:load <file>   run a script in this session
+:reset         start over with a fresh interpreter
+:ast <code>    show the syntax tree without running it
+:tokens <code> show the tokens without running them";
+
+fn run_command(command: &str, lox: &mut Lox) {
+    let (name, arg) = command
+        .split_once(char::is_whitespace)
+        .map_or((command, ""), |(name, arg)| (name, arg.trim()));
+    match (name, arg) {
+        (":env", _) => {
+            for (name, value) in lox.globals() {
+                println!("{name} = {value}");
+            }
+        }
+        (":load", "") => eprintln!("Usage: :load <file>"),
+        (":load", path) => match fs::read_to_string(path) {
+            Ok(source) => {
+                let _ = run(&source, path, lox);
+            }
+            Err(err) => eprintln!("{path}: {err}"),
+        },
+        (":reset", _) => lox.reset(),
+        (":ast", code) => match Lox::parse_line(code) {
+            Ok(stmts) => println!("{}", AstPrinter::new().print(&stmts)),
+            Err(err) => report(&err, code, "<stdin>"),
+        },
+        (":tokens", code) => {
+            if let Err(err) = print_tokens(code, false) {
+                report(&err, code, "<stdin>");
+            }
+        }
+        (":help", _) => println!("{HELP}"),
+        _ => eprintln!("Unknown command '{command}', try :help."),
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    env::home_dir().map(|home| home.join(".lox_history"))
+}
+
+fn run_file(path: String, lox: &mut Lox) {
     if path.is_empty() {
         process::exit(2);
     }
 
-    let mut file = File::open(path).map_err(|err| println!("{err}")).unwrap();
-    let mut buf = String::new();
-    file.read_to_string(&mut buf).unwrap_or_default();
-    match run(buf.as_str(), runner) {
-        true => {
+    let source = read_source(&path);
+    match run(source.as_str(), &path, lox) {
+        Ok(()) => {
             process::exit(0);
         }
-        false => {
-            process::exit(65);
+        Err(code) => {
+            process::exit(code);
         }
     };
 }
 
-fn run(buffer: &str, runner: &mut Interpreter) -> bool {
-    let mut scanner = Scanner::new(buffer);
-    scanner.scan_tokens();
-    let mut parser = Parser::new(scanner.tokens);
-    match parser.parse() {
-        Ok(stmt) => {
-            // println!("{:#?}", stmt);
-            match runner.interpret(stmt) {
-                Ok(_) => true,
-                Err(err) => {
-                    println!("{}", err);
-                    false
+// echoes the value of a trailing expression, nil stays quiet like a statement
+fn run_line(buffer: &str, lox: &mut Lox) {
+    lox.name_source(buffer, "<stdin>");
+    match lox.eval_line(buffer) {
+        Ok(Object::Nil) => {}
+        Ok(value) => println!("{value}"),
+        Err(err) => report(&err, buffer, "<stdin>"),
+    }
+}
+
+fn run(buffer: &str, script: &str, lox: &mut Lox) -> Result<(), i32> {
+    lox.name_source(buffer, script);
+    match lox.eval(buffer) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            report(&err, buffer, script);
+            Err(err.exit_code())
+        }
+    }
+}
+
+fn report(err: &Error, source: &str, script: &str) {
+    match err {
+        Error::Script(diagnostics) => eprintln!("{}", diagnostics.render(source, script)),
+        Error::Io(io_err) => eprintln!("{io_err}"),
+    }
+}
+
+fn read_source(path: &str) -> String {
+    match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("{path}: {err}");
+            process::exit(66);
+        }
+    }
+}
+
+// rewrites scripts in place, with --check only lists the ones that would change
+fn run_fmt(args: &[String]) {
+    let check = args.iter().any(|arg| arg == "--check");
+    let paths = args
+        .iter()
+        .filter(|arg| *arg != "--check")
+        .collect::<Vec<&String>>();
+    if paths.is_empty() {
+        eprintln!("Usage: jlox fmt [--check] <script>...");
+        process::exit(64);
+    }
+
+    let mut failed = None;
+    let mut unformatted = false;
+    for path in paths {
+        let source = read_source(path);
+        match Formatter::format(&source) {
+            Ok(formatted) if formatted == source => {}
+            Ok(_) if check => {
+                println!("{path}");
+                unformatted = true;
+            }
+            Ok(formatted) => {
+                if let Err(err) = fs::write(path, formatted) {
+                    eprintln!("{path}: {err}");
+                    failed = Some(74);
                 }
             }
+            Err(err) => {
+                report(&err, &source, path);
+                failed = Some(err.exit_code());
+            }
         }
+    }
+
+    match failed {
+        Some(code) => process::exit(code),
+        None if unformatted => process::exit(1),
+        None => {}
+    }
+}
+
+fn run_tests(dir: &str) {
+    match harness::run_dir(dir) {
+        Ok(report) => {
+            println!("{report}");
+            process::exit(if report.failed() == 0 { 0 } else { 1 });
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(66);
+        }
+    }
+}
+
+fn dump_ast(path: &str) {
+    let source = read_source(path);
+    match Lox::parse(&source) {
+        Ok(stmts) => println!("{}", AstPrinter::new().print(&stmts)),
         Err(err) => {
-            println!("{}", err);
-            false
+            report(&err, &source, path);
+            process::exit(err.exit_code());
         }
     }
 }
+
+fn dump_tokens(path: &str, json: bool) {
+    let source = read_source(path);
+    if let Err(err) = print_tokens(&source, json) {
+        report(&err, &source, path);
+        process::exit(err.exit_code());
+    }
+}
+
+// tokens are printed even when scanning fails, the errors come back after
+fn print_tokens(source: &str, json: bool) -> Result<(), Error> {
+    let mut scanner = Scanner::new(source);
+    let result = scanner.scan_tokens();
+
+    if json {
+        let tokens = scanner
+            .tokens
+            .iter()
+            .map(|token| format!("  {}", token.to_json()))
+            .collect::<Vec<String>>();
+        println!("[\n{}\n]", tokens.join(",\n"));
+    } else {
+        for token in &scanner.tokens {
+            println!("{}:{} {}", token.line, token.column, token.to_string());
+        }
+    }
+
+    result.map_err(|errs| {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.extend(errs);
+        Error::Script(diagnostics)
+    })
+}
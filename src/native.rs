@@ -0,0 +1,148 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{error::LoxError, interpreter::Interpreter, token_type::Object, traits::LoxCallable};
+
+pub type NativeFn = dyn Fn(&[Object]) -> Result<Object, LoxError>;
+
+// a function implemented by the host, errors without a location are
+// reported at the call site
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    function: Box<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: usize, function: F) -> Self
+    where
+        F: Fn(&[Object]) -> Result<Object, LoxError> + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            arity,
+            function: Box::new(function),
+        }
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+impl fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native fn>")
+    }
+}
+
+impl LoxCallable for NativeFunction {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn call(
+        &self,
+        _interpreter: &mut Interpreter,
+        arguments: Vec<Object>,
+    ) -> Result<Object, LoxError> {
+        (self.function)(&arguments)
+    }
+}
+
+impl TryFrom<&Object> for f64 {
+    type Error = LoxError;
+
+    fn try_from(value: &Object) -> Result<Self, Self::Error> {
+        match value {
+            Object::Number(n) => Ok(*n),
+            _ => Err(LoxError::native(
+                format!("Expected a number but got {}.", value.type_name()).as_str(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<&Object> for String {
+    type Error = LoxError;
+
+    fn try_from(value: &Object) -> Result<Self, Self::Error> {
+        match value {
+            Object::String(s) => Ok(s.clone()),
+            _ => Err(LoxError::native(
+                format!("Expected a string but got {}.", value.type_name()).as_str(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<&Object> for bool {
+    type Error = LoxError;
+
+    fn try_from(value: &Object) -> Result<Self, Self::Error> {
+        match value {
+            Object::Boolean(b) => Ok(*b),
+            _ => Err(LoxError::native(
+                format!("Expected a boolean but got {}.", value.type_name()).as_str(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<&Object> for Rc<RefCell<Vec<Object>>> {
+    type Error = LoxError;
+
+    fn try_from(value: &Object) -> Result<Self, Self::Error> {
+        match value {
+            Object::List(list) => Ok(list.clone()),
+            _ => Err(LoxError::native(
+                format!("Expected a list but got {}.", value.type_name()).as_str(),
+            )),
+        }
+    }
+}
+
+pub fn clock(_arguments: &[Object]) -> Result<Object, LoxError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(Object::Number(now.as_secs_f64()))
+}
+
+// length of a list, or of a string in chars
+pub fn len(arguments: &[Object]) -> Result<Object, LoxError> {
+    match &arguments[0] {
+        Object::String(s) => Ok(Object::Number(s.chars().count() as f64)),
+        Object::List(list) => Ok(Object::Number(list.borrow().len() as f64)),
+        value => Err(LoxError::native(
+            format!("Expected a list or string but got {}.", value.type_name()).as_str(),
+        )),
+    }
+}
+
+// appends in place and hands back the list's new length
+pub fn push(arguments: &[Object]) -> Result<Object, LoxError> {
+    let list = Rc::<RefCell<Vec<Object>>>::try_from(&arguments[0])?;
+    list.borrow_mut().push(arguments[1].clone());
+    let len = list.borrow().len();
+    Ok(Object::Number(len as f64))
+}
+
+pub fn pop(arguments: &[Object]) -> Result<Object, LoxError> {
+    let list = Rc::<RefCell<Vec<Object>>>::try_from(&arguments[0])?;
+    let value = list.borrow_mut().pop();
+    value.ok_or_else(|| LoxError::native("Can't pop from an empty list."))
+}
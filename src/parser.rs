@@ -1,8 +1,17 @@
+use std::rc::Rc;
+
 use crate::{
-    error::LoxError,
-    expr::{Assign, Binary, Expr, Grouping, Literal, Logical, Unary, Variable},
+    diagnostics::Diagnostics,
+    error::{self, LoxError},
+    expr::{
+        Assign, Binary, Call, Expr, Get, Grouping, Index, List, Literal, Logical, Set, SetIndex,
+        Super, This, Unary, Variable,
+    },
     lox_error,
-    stmt::{Block, Expression, If, Print, Stmt, Var, While},
+    stmt::{
+        Block, Break, Class, Continue, Expression, For, Function, If, Print, Return, Stmt, Var,
+        While,
+    },
     token::Token,
     token_type::{Object, TokenType},
 };
@@ -10,11 +19,24 @@ use crate::{
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    // the repl lets a final expression leave off its semicolon
+    repl: bool,
+    diagnostics: Diagnostics,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        Self {
+            tokens,
+            current: 0,
+            repl: false,
+            diagnostics: Diagnostics::new(),
+        }
+    }
+
+    pub fn repl_mode(mut self) -> Self {
+        self.repl = true;
+        self
     }
 
     pub fn peek(&self) -> Token {
@@ -68,10 +90,16 @@ impl Parser {
             let equal = self.previous();
             let value = self.assignment()?;
 
-            if let Expr::Variable(var) = expr {
-                Ok(Expr::Assign(Assign::new(var.name.clone(), value)))
-            } else {
-                Err(lox_error!(equal.clone(), "Invalid assignment target."))
+            match expr {
+                Expr::Variable(var) => Ok(Expr::Assign(Assign::new(var.name.clone(), value))),
+                Expr::Get(get) => Ok(Expr::Set(Set::new(get.object, get.name, value))),
+                Expr::Index(index) => Ok(Expr::SetIndex(SetIndex::new(
+                    index.object,
+                    index.bracket,
+                    index.index,
+                    value,
+                ))),
+                _ => Err(lox_error!(equal.clone(), "Invalid assignment target.")),
             }
         } else {
             Ok(expr)
@@ -132,7 +160,7 @@ impl Parser {
 
     fn term(&mut self) -> Result<Expr, LoxError> {
         let mut expr = self.factor()?;
-        while self.match_types(&[TokenType::Slash, TokenType::Star]) {
+        while self.match_types(&[TokenType::Minus, TokenType::Plus]) {
             let operator = self.previous();
             let right = self.factor()?;
             expr = Expr::Binary(Binary::new(expr, operator, right));
@@ -142,7 +170,7 @@ impl Parser {
 
     fn factor(&mut self) -> Result<Expr, LoxError> {
         let mut expr = self.unary()?;
-        while self.match_types(&[TokenType::Minus, TokenType::Plus]) {
+        while self.match_types(&[TokenType::Slash, TokenType::Star]) {
             let operator = self.previous();
             let right = self.unary()?;
             expr = Expr::Binary(Binary::new(expr, operator, right));
@@ -156,10 +184,53 @@ impl Parser {
             let right = self.unary()?;
             Ok(Expr::Unary(Unary::new(operator, right)))
         } else {
-            self.primary()
+            self.call()
         }
     }
 
+    fn call(&mut self) -> Result<Expr, LoxError> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.match_types(&[TokenType::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_types(&[TokenType::Dot]) {
+                let name =
+                    self.consume(TokenType::Identifiers, "Expect property name after '.'.")?;
+                expr = Expr::Get(Get::new(expr, name));
+            } else if self.match_types(&[TokenType::LeftBracket]) {
+                let index = self.expression()?;
+                let bracket = self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+                expr = Expr::Index(Index::new(expr, bracket, index));
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, LoxError> {
+        let mut arguments = Vec::<Expr>::new();
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                if arguments.len() >= 255 {
+                    return Err(lox_error!(
+                        self.peek(),
+                        "Can't have more than 255 arguments."
+                    ));
+                }
+                arguments.push(self.expression()?);
+                if !self.match_types(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        let paren = self.consume(TokenType::RightParen, "Expect ')' after arguments.")?;
+
+        Ok(Expr::Call(Call::new(callee, paren, arguments)))
+    }
+
     fn primary(&mut self) -> Result<Expr, LoxError> {
         if self.match_types(&[TokenType::False]) {
             return Ok(Expr::Literal(Literal::new(Object::Boolean(false))));
@@ -175,18 +246,38 @@ impl Parser {
                 self.previous().literal.unwrap_or_default(),
             )));
         }
+        if self.match_types(&[TokenType::Super]) {
+            let keyword = self.previous();
+            self.consume(TokenType::Dot, "Expect '.' after 'super'.")?;
+            let method = self.consume(TokenType::Identifiers, "Expect superclass method name.")?;
+            return Ok(Expr::Super(Super::new(keyword, method)));
+        }
+        if self.match_types(&[TokenType::This]) {
+            return Ok(Expr::This(This::new(self.previous())));
+        }
         if self.match_types(&[TokenType::Identifiers]) {
             return Ok(Expr::Variable(Variable::new(self.previous())));
         }
+        if self.match_types(&[TokenType::LeftBracket]) {
+            let mut elements = Vec::<Expr>::new();
+            if !self.check(&TokenType::RightBracket) {
+                loop {
+                    elements.push(self.expression()?);
+                    if !self.match_types(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(TokenType::RightBracket, "Expect ']' after list elements.")?;
+            return Ok(Expr::List(List::new(elements)));
+        }
         if self.match_types(&[TokenType::LeftParen]) {
             let expr = self.expression()?;
             self.consume(TokenType::RightParen, "Expect ')' after expression")?;
             return Ok(Expr::Grouping(Grouping::new(expr)));
         }
 
-        let err = lox_error!(self.peek(), "Expect expression");
-        self.synchronize();
-        Err(err)
+        Err(lox_error!(self.peek(), "Expect expression"))
     }
 
     pub fn consume(&mut self, token_type: TokenType, msg: &str) -> Result<Token, LoxError> {
@@ -221,32 +312,39 @@ impl Parser {
         }
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Stmt>, LoxError> {
-        // self.expression()
+    // keeps going past errors so every diagnostic in the source is reported
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<error::Error>> {
         let mut stmts = Vec::<Stmt>::new();
         while !self.is_at_end() {
-            let stmt = self.declaration()?;
-            stmts.push(stmt);
+            if let Some(stmt) = self.declaration() {
+                stmts.push(stmt);
+            }
+        }
+
+        if self.diagnostics.has_errors() {
+            Err(self.diagnostics.take())
+        } else {
+            Ok(stmts)
         }
-        Ok(stmts)
     }
 
-    fn declaration(&mut self) -> Result<Stmt, LoxError> {
-        if self.match_types(&[TokenType::Var]) {
-            match self.var_declaration() {
-                Ok(stmt) => Ok(stmt),
-                Err(err) => {
-                    self.synchronize();
-                    Err(err)
-                }
-            }
+    fn declaration(&mut self) -> Option<Stmt> {
+        let result = if self.match_types(&[TokenType::Class]) {
+            self.class_declaration()
+        } else if self.match_types(&[TokenType::Fun]) {
+            self.function("function").map(Stmt::Function)
+        } else if self.match_types(&[TokenType::Var]) {
+            self.var_declaration()
         } else {
-            match self.statement() {
-                Ok(stmt) => Ok(stmt),
-                Err(err) => {
-                    self.synchronize();
-                    Err(err)
-                }
+            self.statement()
+        };
+
+        match result {
+            Ok(stmt) => Some(stmt),
+            Err(err) => {
+                self.diagnostics.report(error::Error::Parse(err));
+                self.synchronize();
+                None
             }
         }
     }
@@ -256,7 +354,7 @@ impl Parser {
             return self.print_statement();
         }
         if self.match_types(&[TokenType::LeftBrace]) {
-            return self.block();
+            return Ok(Stmt::Block(Block::new(self.block()?)));
         }
         if self.match_types(&[TokenType::If]) {
             return self.if_statement();
@@ -264,26 +362,112 @@ impl Parser {
         if self.match_types(&[TokenType::While]) {
             return self.while_statement();
         }
+        if self.match_types(&[TokenType::For]) {
+            return self.for_statement();
+        }
+        if self.match_types(&[TokenType::Return]) {
+            return self.return_statement();
+        }
+        if self.match_types(&[TokenType::Break]) {
+            let keyword = self.previous();
+            self.consume(TokenType::SemiColon, "Expect ';' after 'break'.")?;
+            return Ok(Stmt::Break(Break::new(keyword)));
+        }
+        if self.match_types(&[TokenType::Continue]) {
+            let keyword = self.previous();
+            self.consume(TokenType::SemiColon, "Expect ';' after 'continue'.")?;
+            return Ok(Stmt::Continue(Continue::new(keyword)));
+        }
 
         self.expression_statement()
     }
 
     fn print_statement(&mut self) -> Result<Stmt, LoxError> {
+        let keyword = self.previous();
         let value = self.expression()?;
         self.consume(TokenType::SemiColon, "Expect ';' after value.")?;
-        Ok(Stmt::Print(Print::new(value)))
+        Ok(Stmt::Print(Print::new(keyword, value)))
     }
 
     fn expression_statement(&mut self) -> Result<Stmt, LoxError> {
         let expr = self.expression()?;
+        if self.repl && self.is_at_end() {
+            return Ok(Stmt::Expression(Expression::new(expr)));
+        }
         self.consume(TokenType::SemiColon, "Expect ';' after expression.")?;
         Ok(Stmt::Expression(Expression::new(expr)))
     }
 
+    fn return_statement(&mut self) -> Result<Stmt, LoxError> {
+        let keyword = self.previous();
+        let value = if !self.check(&TokenType::SemiColon) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(TokenType::SemiColon, "Expect ';' after return value.")?;
+        Ok(Stmt::Return(Return::new(keyword, value)))
+    }
+
+    fn class_declaration(&mut self) -> Result<Stmt, LoxError> {
+        let name = self.consume(TokenType::Identifiers, "Expect class name.")?;
+
+        let superclass = if self.match_types(&[TokenType::Less]) {
+            self.consume(TokenType::Identifiers, "Expect superclass name.")?;
+            Some(Variable::new(self.previous()))
+        } else {
+            None
+        };
+
+        self.consume(TokenType::LeftBrace, "Expect '{' before class body.")?;
+
+        let mut methods = Vec::<Rc<Function>>::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            methods.push(self.function("method")?);
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after class body.")?;
+
+        Ok(Stmt::Class(Class::new(name, superclass, methods)))
+    }
+
+    fn function(&mut self, kind: &str) -> Result<Rc<Function>, LoxError> {
+        let name = self.consume(
+            TokenType::Identifiers,
+            format!("Expect {kind} name.").as_str(),
+        )?;
+        self.consume(
+            TokenType::LeftParen,
+            format!("Expect '(' after {kind} name.").as_str(),
+        )?;
+        let mut params = Vec::<Token>::new();
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                if params.len() >= 255 {
+                    return Err(lox_error!(
+                        self.peek(),
+                        "Can't have more than 255 parameters."
+                    ));
+                }
+                params.push(self.consume(TokenType::Identifiers, "Expect parameter name.")?);
+                if !self.match_types(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+
+        self.consume(
+            TokenType::LeftBrace,
+            format!("Expect '{{' before {kind} body.").as_str(),
+        )?;
+        let body = self.block()?;
+        Ok(Function::new(name, params, body))
+    }
+
     fn var_declaration(&mut self) -> Result<Stmt, LoxError> {
         let name = self.consume(TokenType::Identifiers, "Expect variable name.")?;
         let initializer = if self.match_types(&[TokenType::Equal]) {
-            self.expression().ok()
+            Some(self.expression()?)
         } else {
             None
         };
@@ -293,21 +477,19 @@ impl Parser {
             TokenType::SemiColon,
             "Expect ';' after variable declaration.",
         )?;
-        match initializer {
-            Some(expr) => Ok(Stmt::Var(Var::new(name, expr))),
-            None => Err(lox_error!(name, "Failed to declare variable.")),
-        }
+        Ok(Stmt::Var(Var::new(name, initializer)))
     }
 
-    fn block(&mut self) -> Result<Stmt, LoxError> {
+    fn block(&mut self) -> Result<Vec<Stmt>, LoxError> {
         let mut stmts = Vec::<Stmt>::new();
         while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
-            let stmt = self.declaration()?;
-            stmts.push(stmt);
+            if let Some(stmt) = self.declaration() {
+                stmts.push(stmt);
+            }
         }
         self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
 
-        Ok(Stmt::Block(Block::new(stmts)))
+        Ok(stmts)
     }
 
     fn if_statement(&mut self) -> Result<Stmt, LoxError> {
@@ -317,7 +499,7 @@ impl Parser {
 
         let then_branch = self.statement()?;
         let else_branch = if self.match_types(&[TokenType::Else]) {
-            self.statement().ok()
+            Some(self.statement()?)
         } else {
             None
         };
@@ -333,4 +515,33 @@ impl Parser {
         let body = self.statement()?;
         Ok(Stmt::While(While::new(condition, body)))
     }
+
+    fn for_statement(&mut self) -> Result<Stmt, LoxError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+
+        let initializer = if self.match_types(&[TokenType::SemiColon]) {
+            None
+        } else if self.match_types(&[TokenType::Var]) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+
+        let condition = if !self.check(&TokenType::SemiColon) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(TokenType::SemiColon, "Expect ';' after loop condition.")?;
+
+        let increment = if !self.check(&TokenType::RightParen) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+        let body = self.statement()?;
+        Ok(Stmt::For(For::new(initializer, condition, increment, body)))
+    }
 }
@@ -0,0 +1,353 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{
+    diagnostics::Diagnostics,
+    error,
+    expr::Expr,
+    interpreter::Interpreter,
+    lox_error,
+    stmt::{Function, Stmt},
+    token::Token,
+    traits::{ExprVisitor, StmtVisitor},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FunctionType {
+    None,
+    Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+pub struct Resolver<'a> {
+    interpreter: &'a mut Interpreter,
+    // name -> finished initializing
+    scopes: Vec<HashMap<String, bool>>,
+    current_function: FunctionType,
+    current_class: ClassType,
+    // number of enclosing loops within the current function
+    loop_depth: usize,
+    diagnostics: Diagnostics,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(interpreter: &'a mut Interpreter) -> Self {
+        Self {
+            interpreter,
+            scopes: Vec::new(),
+            current_function: FunctionType::None,
+            current_class: ClassType::None,
+            loop_depth: 0,
+            diagnostics: Diagnostics::new(),
+        }
+    }
+
+    // keeps going past errors so every problem in the tree is reported
+    pub fn resolve(&mut self, stmts: &[Stmt]) -> Result<(), Vec<error::Error>> {
+        self.resolve_stmts(stmts);
+        if self.diagnostics.has_errors() {
+            Err(self.diagnostics.take())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn resolve_stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.resolve_stmt(stmt);
+        }
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) {
+        stmt.accept(self)
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) {
+        expr.accept(self)
+    }
+
+    fn error(&mut self, token: &Token, msg: &str) {
+        self.diagnostics
+            .report(error::Error::Resolve(lox_error!(token.clone(), msg)));
+    }
+
+    fn resolve_function(&mut self, function: &Function, function_type: FunctionType) {
+        let enclosing_function = self.current_function;
+        let enclosing_loop_depth = self.loop_depth;
+        self.current_function = function_type;
+        self.loop_depth = 0;
+
+        self.begin_scope();
+        for param in function.params.iter() {
+            self.declare(param);
+            self.define(param);
+        }
+        self.resolve_stmts(&function.body);
+        self.end_scope();
+
+        self.current_function = enclosing_function;
+        self.loop_depth = enclosing_loop_depth;
+    }
+
+    fn resolve_loop_body(&mut self, body: &Stmt) {
+        self.loop_depth += 1;
+        self.resolve_stmt(body);
+        self.loop_depth -= 1;
+    }
+
+    fn resolve_local(&mut self, id: usize, name: &Token) {
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if scope.contains_key(&name.lexeme) {
+                self.interpreter.resolve(id, depth);
+                return;
+            }
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Token) {
+        let redeclared = match self.scopes.last_mut() {
+            Some(scope) => scope.insert(name.lexeme.clone(), false).is_some(),
+            None => false,
+        };
+        if redeclared {
+            self.error(name, "Already a variable with this name in this scope.");
+        }
+    }
+
+    fn define(&mut self, name: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), true);
+        }
+    }
+}
+
+impl ExprVisitor<()> for Resolver<'_> {
+    fn visit_binary(&mut self, expr: &crate::expr::Binary) {
+        self.resolve_expr(&expr.left);
+        self.resolve_expr(&expr.right);
+    }
+
+    fn visit_grouping(&mut self, expr: &crate::expr::Grouping) {
+        self.resolve_expr(&expr.expression);
+    }
+
+    fn visit_unary(&mut self, expr: &crate::expr::Unary) {
+        self.resolve_expr(&expr.right);
+    }
+
+    fn visit_literal(&mut self, _expr: &crate::expr::Literal) {}
+
+    fn visit_variable(&mut self, expr: &crate::expr::Variable) {
+        let uninitialized = self
+            .scopes
+            .last()
+            .is_some_and(|scope| scope.get(&expr.name.lexeme) == Some(&false));
+        if uninitialized {
+            self.error(
+                &expr.name,
+                "Can't read local variable in its own initializer.",
+            );
+        }
+        self.resolve_local(expr.id, &expr.name);
+    }
+
+    fn visit_assign(&mut self, expr: &crate::expr::Assign) {
+        self.resolve_expr(&expr.value);
+        self.resolve_local(expr.id, &expr.name);
+    }
+
+    fn visit_get(&mut self, expr: &crate::expr::Get) {
+        self.resolve_expr(&expr.object);
+    }
+
+    fn visit_set(&mut self, expr: &crate::expr::Set) {
+        self.resolve_expr(&expr.value);
+        self.resolve_expr(&expr.object);
+    }
+
+    fn visit_list(&mut self, expr: &crate::expr::List) {
+        for element in expr.elements.iter() {
+            self.resolve_expr(element);
+        }
+    }
+
+    fn visit_index(&mut self, expr: &crate::expr::Index) {
+        self.resolve_expr(&expr.object);
+        self.resolve_expr(&expr.index);
+    }
+
+    fn visit_set_index(&mut self, expr: &crate::expr::SetIndex) {
+        self.resolve_expr(&expr.object);
+        self.resolve_expr(&expr.index);
+        self.resolve_expr(&expr.value);
+    }
+
+    fn visit_this(&mut self, expr: &crate::expr::This) {
+        if self.current_class == ClassType::None {
+            self.error(&expr.keyword, "Can't use 'this' outside of a class.");
+            return;
+        }
+        self.resolve_local(expr.id, &expr.keyword);
+    }
+
+    fn visit_super(&mut self, expr: &crate::expr::Super) {
+        match self.current_class {
+            ClassType::None => self.error(&expr.keyword, "Can't use 'super' outside of a class."),
+            ClassType::Class => self.error(
+                &expr.keyword,
+                "Can't use 'super' in a class with no superclass.",
+            ),
+            ClassType::Subclass => self.resolve_local(expr.id, &expr.keyword),
+        }
+    }
+
+    fn visit_logical(&mut self, expr: &crate::expr::Logical) {
+        self.resolve_expr(&expr.left);
+        self.resolve_expr(&expr.right);
+    }
+
+    fn visit_call(&mut self, expr: &crate::expr::Call) {
+        self.resolve_expr(&expr.callee);
+        for argument in expr.arguments.iter() {
+            self.resolve_expr(argument);
+        }
+    }
+}
+
+impl StmtVisitor<()> for Resolver<'_> {
+    fn visit_print(&mut self, stmt: &crate::stmt::Print) {
+        self.resolve_expr(&stmt.expression);
+    }
+
+    fn visit_expression(&mut self, stmt: &crate::stmt::Expression) {
+        self.resolve_expr(&stmt.expression);
+    }
+
+    fn visit_var(&mut self, stmt: &crate::stmt::Var) {
+        self.declare(&stmt.token);
+        if let Some(initializer) = &stmt.initializer {
+            self.resolve_expr(initializer);
+        }
+        self.define(&stmt.token);
+    }
+
+    fn visit_block(&mut self, stmt: &crate::stmt::Block) {
+        self.begin_scope();
+        self.resolve_stmts(&stmt.statements);
+        self.end_scope();
+    }
+
+    fn visit_if(&mut self, stmt: &crate::stmt::If) {
+        self.resolve_expr(&stmt.condition);
+        self.resolve_stmt(&stmt.then_branch);
+        if let Some(else_branch) = &stmt.else_branch {
+            self.resolve_stmt(else_branch);
+        }
+    }
+
+    fn visit_while(&mut self, stmt: &crate::stmt::While) {
+        self.resolve_expr(&stmt.condition);
+        self.resolve_loop_body(&stmt.body);
+    }
+
+    fn visit_for(&mut self, stmt: &crate::stmt::For) {
+        self.begin_scope();
+        if let Some(initializer) = &stmt.initializer {
+            self.resolve_stmt(initializer);
+        }
+        if let Some(condition) = &stmt.condition {
+            self.resolve_expr(condition);
+        }
+        if let Some(increment) = &stmt.increment {
+            self.resolve_expr(increment);
+        }
+        self.resolve_loop_body(&stmt.body);
+        self.end_scope();
+    }
+
+    fn visit_function(&mut self, stmt: &Rc<Function>) {
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+        self.resolve_function(stmt, FunctionType::Function);
+    }
+
+    fn visit_return(&mut self, stmt: &crate::stmt::Return) {
+        if self.current_function == FunctionType::None {
+            self.error(&stmt.keyword, "Can't return from top-level code.");
+        }
+        if let Some(value) = &stmt.value {
+            if self.current_function == FunctionType::Initializer {
+                self.error(&stmt.keyword, "Can't return a value from an initializer.");
+            }
+            self.resolve_expr(value);
+        }
+    }
+
+    fn visit_class(&mut self, stmt: &crate::stmt::Class) {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Class;
+
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+
+        if let Some(superclass) = &stmt.superclass {
+            if superclass.name.lexeme == stmt.name.lexeme {
+                self.error(&superclass.name, "A class can't inherit from itself.");
+            }
+            self.current_class = ClassType::Subclass;
+            self.visit_variable(superclass);
+
+            self.begin_scope();
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.insert("super".to_string(), true);
+            }
+        }
+
+        self.begin_scope();
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert("this".to_string(), true);
+        }
+        for method in stmt.methods.iter() {
+            let declaration = if method.name.lexeme == "init" {
+                FunctionType::Initializer
+            } else {
+                FunctionType::Method
+            };
+            self.resolve_function(method, declaration);
+        }
+        self.end_scope();
+
+        if stmt.superclass.is_some() {
+            self.end_scope();
+        }
+
+        self.current_class = enclosing_class;
+    }
+
+    fn visit_break(&mut self, stmt: &crate::stmt::Break) {
+        if self.loop_depth == 0 {
+            self.error(&stmt.keyword, "Can't use 'break' outside of a loop.");
+        }
+    }
+
+    fn visit_continue(&mut self, stmt: &crate::stmt::Continue) {
+        if self.loop_depth == 0 {
+            self.error(&stmt.keyword, "Can't use 'continue' outside of a loop.");
+        }
+    }
+}
@@ -1,36 +1,63 @@
 use crate::{
-    lox_error_report,
-    token::Token,
+    diagnostics::Diagnostics,
+    error::{self, LoxError},
+    lox_error,
+    token::{self, Span, Token},
     token_type::{Object, TokenType},
     traits::KeywordIdentidiers,
-    LoxError,
 };
 
 #[derive(Debug)]
 pub struct Scanner {
     source: String,
+    source_id: u64,
     char_vec: Vec<char>,
+    // byte offset of every char, plus one past the end
+    byte_offsets: Vec<usize>,
     start: usize,
     current: usize,
     line: usize,
+    start_line: usize,
     pub tokens: Vec<Token>,
+    // `//` comments, only collected when asked for, e.g. by the formatter
+    pub comments: Vec<Token>,
+    keep_comments: bool,
+    diagnostics: Diagnostics,
 }
 
 impl Scanner {
     pub fn new(source: &str) -> Self {
+        let mut byte_offsets = source
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .collect::<Vec<usize>>();
+        byte_offsets.push(source.len());
         Self {
             source: source.to_string(),
+            source_id: token::source_id(source),
             char_vec: source.chars().collect(),
+            byte_offsets,
             start: 0,
             current: 0,
             line: 1,
+            start_line: 1,
             tokens: Vec::new(),
+            comments: Vec::new(),
+            keep_comments: false,
+            diagnostics: Diagnostics::new(),
         }
     }
 
-    pub fn scan_tokens(&mut self) {
+    pub fn with_comments(mut self) -> Self {
+        self.keep_comments = true;
+        self
+    }
+
+    // tokens are kept even on failure so the parser can report its own errors too
+    pub fn scan_tokens(&mut self) -> Result<(), Vec<error::Error>> {
         loop {
             self.start = self.current;
+            self.start_line = self.line;
             let char = self.char_vec.get(self.current).unwrap_or(&' ');
             self.current += 1;
             match char {
@@ -38,6 +65,8 @@ impl Scanner {
                 ')' => self.add_token(TokenType::RightParen),
                 '{' => self.add_token(TokenType::LeftBrace),
                 '}' => self.add_token(TokenType::RightBrace),
+                '[' => self.add_token(TokenType::LeftBracket),
+                ']' => self.add_token(TokenType::RightBracket),
                 ',' => self.add_token(TokenType::Comma),
                 '.' => self.add_token(TokenType::Dot),
                 '-' => self.add_token(TokenType::Minus),
@@ -81,6 +110,9 @@ impl Scanner {
                         while self.peek() != '\n' && !self.at_end() {
                             self.current += 1;
                         }
+                        if self.keep_comments {
+                            self.add_comment();
+                        }
                     // } else if self.match_char('*') {
                     //     while self.peek() != '*' && self.double_peek() != '/' && !self.at_end() {
                     //         if self.peek() == '\n' {
@@ -104,11 +136,8 @@ impl Scanner {
                     } else if char.is_alphabetic() {
                         let _ = self.handle_identifier();
                     } else {
-                        lox_error_report!(
-                            self.line,
-                            format!("{char}{}", self.peek()).as_str(),
-                            "Unexpected character"
-                        );
+                        let where_err = format!("at '{char}'");
+                        self.error(&where_err, "Unexpected character.");
                     }
                 }
             }
@@ -117,13 +146,61 @@ impl Scanner {
             }
         }
         self.append_eof();
+
+        if self.diagnostics.has_errors() {
+            Err(self.diagnostics.take())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn error(&mut self, where_err: &str, msg: &str) {
+        let err = self.error_here(where_err, msg);
+        self.diagnostics.report(error::Error::Scan(err));
+    }
+
+    // an error spanning the token being scanned
+    fn error_here(&self, where_err: &str, msg: &str) -> LoxError {
+        let span = Span {
+            source: self.source_id,
+            offset: self.byte_offsets[self.start],
+            column: self.column(),
+            length: self.current.min(self.char_vec.len()) - self.start,
+        };
+        lox_error!(self.start_line, where_err, msg).with_span(span)
     }
 
     fn append_eof(&mut self) {
-        self.add(Token::new(TokenType::Eof, "".to_string(), None, self.line));
+        self.start = self.char_vec.len();
+        let column = self.column();
+        self.add(Token::new(
+            TokenType::Eof,
+            "".to_string(),
+            None,
+            self.line,
+            column,
+            self.source.len(),
+        ));
+    }
+
+    fn add_comment(&mut self) {
+        let text = self.slice(self.start, self.current);
+        let column = self.column();
+        let offset = self.byte_offsets[self.start];
+        let mut comment = Token::new(
+            TokenType::Comment,
+            text.trim_end().to_string(),
+            None,
+            self.start_line,
+            column,
+            offset,
+        );
+        comment.source = self.source_id;
+        self.comments.push(comment);
     }
 
-    pub fn add(&mut self, tok: Token) {
+    pub fn add(&mut self, mut tok: Token) {
+        tok.source = self.source_id;
         self.tokens.push(tok);
     }
 
@@ -132,9 +209,32 @@ impl Scanner {
     }
 
     pub fn add_tokens(&mut self, token_type: TokenType, literal: Option<Object>) {
-        let text = self.source[self.start..self.current].to_string();
-        // println!("{text}");
-        self.add(Token::new(token_type, text, literal, self.line));
+        let text = self.slice(self.start, self.current);
+        let column = self.column();
+        let offset = self.byte_offsets[self.start];
+        self.add(Token::new(
+            token_type,
+            text,
+            literal,
+            self.start_line,
+            column,
+            offset,
+        ));
+    }
+
+    fn slice(&self, start: usize, end: usize) -> String {
+        let end = end.min(self.char_vec.len());
+        self.source[self.byte_offsets[start]..self.byte_offsets[end]].to_string()
+    }
+
+    // 1-based column of the current lexeme's first char
+    fn column(&self) -> usize {
+        self.char_vec[..self.start]
+            .iter()
+            .rev()
+            .take_while(|c| **c != '\n')
+            .count()
+            + 1
     }
 
     pub fn match_char(&mut self, expected: char) -> bool {
@@ -142,9 +242,9 @@ impl Scanner {
             false
         } else if self.char_vec.get(self.current).unwrap_or(&' ') == &expected {
             self.current += 1;
-            return true;
+            true
         } else {
-            return false;
+            false
         }
     }
 
@@ -156,7 +256,7 @@ impl Scanner {
         if self.at_end() {
             '\0'
         } else {
-            return *self.char_vec.get(self.current).unwrap_or(&' ');
+            *self.char_vec.get(self.current).unwrap_or(&' ')
         }
     }
 
@@ -164,13 +264,11 @@ impl Scanner {
         if self.current + 1 >= self.char_vec.len() {
             '\0'
         } else {
-            return *self.char_vec.get(self.current + 1).unwrap_or(&' ');
+            *self.char_vec.get(self.current + 1).unwrap_or(&' ')
         }
     }
 
     pub fn handle_string(&mut self) -> Result<(), LoxError> {
-        // consume " opening
-        self.current += 1;
         loop {
             if self.peek() != '"' && !self.at_end() {
                 // println!("Here");
@@ -185,11 +283,14 @@ impl Scanner {
         }
 
         if self.at_end() {
-            lox_error_report!(self.line, "", "Unterminated String");
+            let mut err = self.error_here("", "Unterminated string.");
+            err.at_end = true;
+            self.diagnostics.report(error::Error::Scan(err));
+            return Ok(());
         }
 
         self.current += 1;
-        let text = self.source[self.start + 1..self.current - 1].to_string();
+        let text = self.slice(self.start + 1, self.current - 1);
         self.add_tokens(TokenType::String, Some(Object::String(text)));
         Ok(())
     }
@@ -213,8 +314,8 @@ impl Scanner {
                 }
             }
         }
-        let number = self.source[self.start..self.current]
-            .to_string()
+        let number = self
+            .slice(self.start, self.current)
             .parse::<f64>()
             .unwrap_or_default();
         self.add_tokens(TokenType::Number, Some(Object::Number(number)));
@@ -230,7 +331,7 @@ impl Scanner {
             }
         }
 
-        let identifier = self.source[self.start..self.current].to_string().identify();
+        let identifier = self.slice(self.start, self.current).identify();
         self.add_token(identifier);
         Ok(())
     }
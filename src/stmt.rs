@@ -1,4 +1,10 @@
-use crate::{expr::Expr, token::Token, traits::StmtVisitor};
+use std::rc::Rc;
+
+use crate::{
+    expr::{Expr, Variable},
+    token::Token,
+    traits::StmtVisitor,
+};
 
 #[derive(Debug, Clone)]
 pub enum Stmt {
@@ -8,6 +14,12 @@ pub enum Stmt {
     Block(Box<Block>),
     If(Box<If>),
     While(Box<While>),
+    For(Box<For>),
+    Function(Rc<Function>),
+    Return(Box<Return>),
+    Class(Box<Class>),
+    Break(Box<Break>),
+    Continue(Box<Continue>),
 }
 
 impl Stmt {
@@ -19,18 +31,28 @@ impl Stmt {
             Self::Block(stmt) => visitor.visit_block(stmt),
             Self::If(stmt) => visitor.visit_if(stmt),
             Self::While(stmt) => visitor.visit_while(stmt),
+            Self::For(stmt) => visitor.visit_for(stmt),
+            Self::Function(stmt) => visitor.visit_function(stmt),
+            Self::Return(stmt) => visitor.visit_return(stmt),
+            Self::Class(stmt) => visitor.visit_class(stmt),
+            Self::Break(stmt) => visitor.visit_break(stmt),
+            Self::Continue(stmt) => visitor.visit_continue(stmt),
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Print {
+    pub keyword: Token,
     pub expression: Expr,
 }
 
 impl Print {
-    pub fn new(expression: Expr) -> Box<Self> {
-        Box::new(Self { expression })
+    pub fn new(keyword: Token, expression: Expr) -> Box<Self> {
+        Box::new(Self {
+            keyword,
+            expression,
+        })
     }
 }
 
@@ -48,11 +70,12 @@ impl Expression {
 #[derive(Debug, Clone)]
 pub struct Var {
     pub token: Token,
-    pub initializer: Expr,
+    // nil when left out, as in `var a;`
+    pub initializer: Option<Expr>,
 }
 
 impl Var {
-    pub fn new(token: Token, initializer: Expr) -> Box<Self> {
+    pub fn new(token: Token, initializer: Option<Expr>) -> Box<Self> {
         Box::new(Self { token, initializer })
     }
 }
@@ -98,4 +121,93 @@ impl While {
 }
 
 #[derive(Debug, Clone)]
-pub struct For {}
+pub struct For {
+    pub initializer: Option<Stmt>,
+    pub condition: Option<Expr>,
+    pub increment: Option<Expr>,
+    pub body: Stmt,
+}
+
+impl For {
+    pub fn new(
+        initializer: Option<Stmt>,
+        condition: Option<Expr>,
+        increment: Option<Expr>,
+        body: Stmt,
+    ) -> Box<Self> {
+        Box::new(Self {
+            initializer,
+            condition,
+            increment,
+            body,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: Token,
+    pub params: Vec<Token>,
+    pub body: Vec<Stmt>,
+}
+
+impl Function {
+    pub fn new(name: Token, params: Vec<Token>, body: Vec<Stmt>) -> Rc<Self> {
+        Rc::new(Self { name, params, body })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Return {
+    pub keyword: Token,
+    pub value: Option<Expr>,
+}
+
+impl Return {
+    pub fn new(keyword: Token, value: Option<Expr>) -> Box<Self> {
+        Box::new(Self { keyword, value })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Class {
+    pub name: Token,
+    pub superclass: Option<Rc<Variable>>,
+    pub methods: Vec<Rc<Function>>,
+}
+
+impl Class {
+    pub fn new(
+        name: Token,
+        superclass: Option<Rc<Variable>>,
+        methods: Vec<Rc<Function>>,
+    ) -> Box<Self> {
+        Box::new(Self {
+            name,
+            superclass,
+            methods,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Break {
+    pub keyword: Token,
+}
+
+impl Break {
+    pub fn new(keyword: Token) -> Box<Self> {
+        Box::new(Self { keyword })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Continue {
+    pub keyword: Token,
+}
+
+impl Continue {
+    pub fn new(keyword: Token) -> Box<Self> {
+        Box::new(Self { keyword })
+    }
+}
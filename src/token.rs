@@ -1,11 +1,34 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
 use crate::token_type::{Object, TokenType};
 
+// fingerprint of the text a token was scanned from. the repl and :load run
+// many sources through one interpreter, a span is only looked up in its own
+pub fn source_id(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+// location of a piece of source, offset is in bytes, column and length in chars
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+    pub source: u64,
+    pub offset: usize,
+    pub column: usize,
+    pub length: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
     pub literal: Option<Object>,
     pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+    pub length: usize,
+    pub source: u64,
 }
 
 impl Default for Token {
@@ -15,6 +38,10 @@ impl Default for Token {
             lexeme: String::new(),
             literal: None,
             line: 0,
+            column: 0,
+            offset: 0,
+            length: 0,
+            source: 0,
         }
     }
 }
@@ -25,12 +52,28 @@ impl Token {
         lexeme: String,
         literal: Option<Object>,
         line: usize,
+        column: usize,
+        offset: usize,
     ) -> Self {
+        let length = lexeme.chars().count();
         Self {
             token_type,
             lexeme,
             literal,
             line,
+            column,
+            offset,
+            length,
+            source: 0,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        Span {
+            source: self.source,
+            offset: self.offset,
+            column: self.column,
+            length: self.length,
         }
     }
 
@@ -43,4 +86,42 @@ impl Token {
             self.literal.clone().unwrap_or_default()
         )
     }
+
+    // a single json object, for tools that consume the scanner's output
+    pub fn to_json(&self) -> String {
+        let literal = match &self.literal {
+            Some(Object::Number(n)) => n.to_string(),
+            Some(Object::String(s)) => json_string(s),
+            Some(Object::Boolean(b)) => b.to_string(),
+            _ => "null".to_string(),
+        };
+        format!(
+            "{{\"type\": {}, \"lexeme\": {}, \"literal\": {}, \"line\": {}, \"column\": {}, \"offset\": {}, \"length\": {}}}",
+            json_string(&format!("{:?}", self.token_type)),
+            json_string(&self.lexeme),
+            literal,
+            self.line,
+            self.column,
+            self.offset,
+            self.length
+        )
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
 }
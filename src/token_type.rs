@@ -1,6 +1,13 @@
-use std::fmt;
+use std::{cell::RefCell, fmt, rc::Rc};
 
-use crate::{error::LoxError, lox_error, token::Token};
+use crate::{
+    class::{LoxClass, LoxInstance},
+    error::LoxError,
+    function::LoxFunction,
+    lox_error,
+    native::NativeFunction,
+    token::Token,
+};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum TokenType {
@@ -9,6 +16,8 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
     Minus,
@@ -31,7 +40,9 @@ pub enum TokenType {
     Number,
     // Keywords
     And,
+    Break,
     Class,
+    Continue,
     Else,
     False,
     Fun,
@@ -46,6 +57,8 @@ pub enum TokenType {
     True,
     Var,
     While,
+    // Trivia, only kept when a scanner asks for comments
+    Comment,
     Eof,
 }
 
@@ -56,7 +69,12 @@ pub enum Object {
     #[default]
     Nil,
     Boolean(bool),
-    Function(String),
+    Function(Rc<LoxFunction>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<RefCell<LoxInstance>>),
+    Native(Rc<NativeFunction>),
+    // shared, so every alias sees a push or an assignment through another
+    List(Rc<RefCell<Vec<Object>>>),
 }
 
 impl fmt::Display for Object {
@@ -66,13 +84,56 @@ impl fmt::Display for Object {
             Self::String(s) => write!(f, "{s}"),
             Self::Nil => write!(f, "nil"),
             Self::Boolean(b) => write!(f, "{b}"),
-            // Self::Function(f) => write!(f, "{f}"),
-            _ => unimplemented!(),
+            Self::Function(fun) => write!(f, "{fun}"),
+            Self::Class(class) => write!(f, "{class}"),
+            Self::Instance(instance) => write!(f, "{}", instance.borrow()),
+            Self::Native(native) => write!(f, "{native}"),
+            Self::List(list) => write_list(f, list, &mut Vec::new()),
         }
     }
 }
 
+// `open` holds the lists being printed further up, a list that contains
+// itself shows the inner occurrence as [...] instead of recursing forever
+fn write_list(
+    f: &mut fmt::Formatter<'_>,
+    list: &Rc<RefCell<Vec<Object>>>,
+    open: &mut Vec<*const RefCell<Vec<Object>>>,
+) -> fmt::Result {
+    let ptr = Rc::as_ptr(list);
+    if open.contains(&ptr) {
+        return write!(f, "[...]");
+    }
+
+    open.push(ptr);
+    write!(f, "[")?;
+    for (i, element) in list.borrow().iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        match element {
+            Object::List(inner) => write_list(f, inner, open)?,
+            element => write!(f, "{element}")?,
+        }
+    }
+    open.pop();
+    write!(f, "]")
+}
+
 impl Object {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Number(_) => "number",
+            Self::String(_) => "string",
+            Self::Nil => "nil",
+            Self::Boolean(_) => "boolean",
+            Self::Function(_) | Self::Native(_) => "function",
+            Self::Class(_) => "class",
+            Self::Instance(_) => "instance",
+            Self::List(_) => "list",
+        }
+    }
+
     pub fn is_truthy(&self) -> bool {
         match self {
             Self::Nil => false,
@@ -109,7 +170,7 @@ impl Object {
 
     pub fn negate_wrap(&self) -> Self {
         match self.get_number() {
-            Some(n) => Object::Number(n * -1.),
+            Some(n) => Object::Number(-n),
             None => Object::Nil,
         }
     }
@@ -122,7 +183,29 @@ impl Object {
         Object::String(value.to_string())
     }
 
+    // values of different types are never equal, objects compare by identity
+    pub fn is_equal(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Nil, Self::Nil) => true,
+            (Self::Boolean(l), Self::Boolean(r)) => l == r,
+            (Self::Number(l), Self::Number(r)) => l == r,
+            (Self::String(l), Self::String(r)) => l == r,
+            (Self::Function(l), Self::Function(r)) => Rc::ptr_eq(l, r),
+            (Self::Class(l), Self::Class(r)) => Rc::ptr_eq(l, r),
+            (Self::Instance(l), Self::Instance(r)) => Rc::ptr_eq(l, r),
+            (Self::Native(l), Self::Native(r)) => Rc::ptr_eq(l, r),
+            (Self::List(l), Self::List(r)) => Rc::ptr_eq(l, r),
+            _ => false,
+        }
+    }
+
     pub fn literal_eval(&self, right: &Self, operator: Token) -> Result<Self, LoxError> {
+        match operator.token_type {
+            TokenType::EqualEqual => return Ok(Self::Boolean(self.is_equal(right))),
+            TokenType::BangEqual => return Ok(Self::Boolean(!self.is_equal(right))),
+            _ => {}
+        }
+
         match (self, right) {
             (Self::String(l), Self::Number(r)) => {
                 if operator.token_type == TokenType::Plus {
@@ -146,8 +229,6 @@ impl Object {
             }
             (Self::String(l), Self::String(r)) => match operator.token_type {
                 TokenType::Plus => Ok(Self::String(format!("{}{}", l, r))),
-                TokenType::EqualEqual => Ok(Self::Boolean(l == r)),
-                TokenType::BangEqual => Ok(Self::Boolean(l != r)),
                 _ => Err(lox_error!(
                     operator,
                     "String and string doesnt support operation"
@@ -162,8 +243,6 @@ impl Object {
                 TokenType::GreaterEqual => Ok(Self::Boolean(l >= r)),
                 TokenType::Less => Ok(Self::Boolean(l < r)),
                 TokenType::LessEqual => Ok(Self::Boolean(l <= r)),
-                TokenType::BangEqual => Ok(Self::Boolean(l != r)),
-                TokenType::EqualEqual => Ok(Self::Boolean(l == r)),
                 _ => Err(lox_error!(
                     operator,
                     "Number and number doesnt support operation"
@@ -1,7 +1,16 @@
+use std::rc::Rc;
+
 use crate::{
-    expr::{Assign, Binary, Grouping, Literal, Logical, Unary, Variable},
-    stmt::{Block, Expression, If, Print, Var, While},
-    token_type::TokenType,
+    error::LoxError,
+    expr::{
+        Assign, Binary, Call, Get, Grouping, Index, List, Literal, Logical, Set, SetIndex, Super,
+        This, Unary, Variable,
+    },
+    interpreter::Interpreter,
+    stmt::{
+        Block, Break, Class, Continue, Expression, For, Function, If, Print, Return, Var, While,
+    },
+    token_type::{Object, TokenType},
 };
 
 pub trait KeywordIdentidiers {
@@ -12,7 +21,9 @@ impl KeywordIdentidiers for String {
     fn identify(&self) -> TokenType {
         match self.as_str() {
             "and" => TokenType::And,
+            "break" => TokenType::Break,
             "class" => TokenType::Class,
+            "continue" => TokenType::Continue,
             "else" => TokenType::Else,
             "false" => TokenType::False,
             "for" => TokenType::For,
@@ -40,6 +51,14 @@ pub trait ExprVisitor<R> {
     fn visit_variable(&mut self, expr: &Variable) -> R;
     fn visit_assign(&mut self, expr: &Assign) -> R;
     fn visit_logical(&mut self, expr: &Logical) -> R;
+    fn visit_call(&mut self, expr: &Call) -> R;
+    fn visit_get(&mut self, expr: &Get) -> R;
+    fn visit_set(&mut self, expr: &Set) -> R;
+    fn visit_this(&mut self, expr: &This) -> R;
+    fn visit_super(&mut self, expr: &Super) -> R;
+    fn visit_list(&mut self, expr: &List) -> R;
+    fn visit_index(&mut self, expr: &Index) -> R;
+    fn visit_set_index(&mut self, expr: &SetIndex) -> R;
 }
 
 pub trait StmtVisitor<R> {
@@ -49,4 +68,20 @@ pub trait StmtVisitor<R> {
     fn visit_block(&mut self, stmt: &Block) -> R;
     fn visit_if(&mut self, stmt: &If) -> R;
     fn visit_while(&mut self, stmt: &While) -> R;
+    fn visit_for(&mut self, stmt: &For) -> R;
+    fn visit_function(&mut self, stmt: &Rc<Function>) -> R;
+    fn visit_return(&mut self, stmt: &Return) -> R;
+    fn visit_class(&mut self, stmt: &Class) -> R;
+    fn visit_break(&mut self, stmt: &Break) -> R;
+    fn visit_continue(&mut self, stmt: &Continue) -> R;
+}
+
+pub trait LoxCallable {
+    fn name(&self) -> &str;
+    fn arity(&self) -> usize;
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Object>,
+    ) -> Result<Object, LoxError>;
 }
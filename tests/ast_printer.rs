@@ -0,0 +1,51 @@
+use lox_ast::{AstPrinter, Lox};
+
+fn print(source: &str) -> String {
+    let stmts = Lox::parse(source).expect("source should parse");
+    AstPrinter::new().print(&stmts)
+}
+
+#[test]
+fn prints_expressions_with_precedence_made_explicit() {
+    assert_eq!(print("print 1 + 2 * 3;"), "(print (+ 1 (* 2 3)))");
+    assert_eq!(
+        print("-(1 - 2) == !true;"),
+        "(; (== (- (group (- 1 2))) (! true)))"
+    );
+    assert_eq!(
+        print("a = b or c and \"d\";"),
+        "(; (= a (or b (and c \"d\"))))"
+    );
+}
+
+#[test]
+fn prints_one_statement_per_line() {
+    let source = "var a = 1;\nvar b;\n{ print a; }";
+    assert_eq!(print(source), "(var a 1)\n(var b)\n(block (print a))");
+}
+
+#[test]
+fn prints_control_flow_with_missing_clauses_as_underscores() {
+    assert_eq!(
+        print("if (a) print 1; else print 2;"),
+        "(if a (print 1) (print 2))"
+    );
+    assert_eq!(print("while (a) break;"), "(while a (break))");
+    assert_eq!(print("for (;;) continue;"), "(for _ _ _ (continue))");
+}
+
+#[test]
+fn prints_functions_classes_and_lists() {
+    assert_eq!(
+        print("fun add(a, b) { return a + b; }"),
+        "(fun add (a b) (return (+ a b)))"
+    );
+    assert_eq!(
+        print("class B < A { m() { return super.m(this.x); } }"),
+        "(class B < A (method m () (return (call (. super m) (. this x)))))"
+    );
+    assert_eq!(
+        print("xs[0] = [1, 2][1];"),
+        "(; (= ([] xs 0) ([] (list 1 2) 1)))"
+    );
+}
@@ -0,0 +1,125 @@
+use std::io;
+
+use lox_ast::{Diagnostics, Error, Lox};
+
+fn diagnostics_for(lox: &mut Lox, source: &str) -> Diagnostics {
+    match lox.eval(source) {
+        Err(Error::Script(diagnostics)) => diagnostics,
+        other => panic!("expected a script error, got {other:?}"),
+    }
+}
+
+#[test]
+fn underlines_the_offending_token() {
+    let mut lox = Lox::new();
+    let source = "print nil - 1;\n";
+    let diagnostics = diagnostics_for(&mut lox, source);
+    let rendered = diagnostics.errors()[0].render(source);
+    assert_eq!(
+        rendered,
+        "error[E0004]: [line 1] Error at '-': Unexpected combination\n 1 | print nil - 1;\n   |           ^"
+    );
+}
+
+#[test]
+fn skips_the_snippet_for_an_error_from_another_source() {
+    let mut lox = Lox::new();
+    lox.set_output(io::sink());
+    lox.eval("fun f() { return nil - 1; }").unwrap();
+
+    // the byte offset of `-` lands inside a multi-byte char here
+    let source = "print \"aééééééééé\"; f();";
+    let diagnostics = diagnostics_for(&mut lox, source);
+    let rendered = diagnostics.errors()[0].render(source);
+    assert_eq!(
+        rendered,
+        "error[E0004]: [line 1] Error at '-': Unexpected combination"
+    );
+}
+
+#[test]
+fn tags_each_stage_with_its_code() {
+    let cases = [
+        (
+            "print \"open;",
+            "error[E0001]: [line 1] Error: Unterminated string.",
+        ),
+        (
+            "print ;",
+            "error[E0002]: [line 1] Error at ';': Expect expression",
+        ),
+        (
+            "return 1;",
+            "error[E0003]: [line 1] Error at 'return': Can't return from top-level code.",
+        ),
+        (
+            "print nil - 1;",
+            "error[E0004]: [line 1] Error at '-': Unexpected combination",
+        ),
+    ];
+    for (source, expected) in cases {
+        let diagnostics = diagnostics_for(&mut Lox::new(), source);
+        let rendered = diagnostics.errors()[0].render(source);
+        assert_eq!(rendered.lines().next(), Some(expected), "{source}");
+    }
+}
+
+#[test]
+fn exits_with_the_code_of_the_first_failing_stage() {
+    let cases = [
+        ("print \"open;", 65),
+        ("print ;", 65),
+        ("return 1;", 65),
+        ("print nil - 1;", 70),
+    ];
+    for (source, expected) in cases {
+        let diagnostics = diagnostics_for(&mut Lox::new(), source);
+        assert_eq!(diagnostics.exit_code(), expected, "{source}");
+        assert_eq!(Error::Script(diagnostics).exit_code(), expected, "{source}");
+    }
+}
+
+#[test]
+fn traces_name_the_source_each_frame_ran_in() {
+    let mut lox = Lox::new();
+    let lib = "fun f() {\n  return nil - 1;\n}";
+    lox.name_source(lib, "lib.lox");
+    lox.eval(lib).unwrap();
+
+    let diagnostics = diagnostics_for(&mut lox, "\nf();");
+    assert_eq!(
+        diagnostics.errors()[0].inner().render_trace("<stdin>"),
+        "    at f (lib.lox:2)\n    at <script> (<stdin>:2)"
+    );
+}
+
+#[test]
+fn runaway_recursion_fails_on_a_default_sized_thread() {
+    // spawned threads get 2 MiB unless RUST_MIN_STACK says otherwise
+    let msg = std::thread::spawn(|| {
+        let mut lox = Lox::new();
+        let diagnostics = diagnostics_for(&mut lox, "fun f(n) { return f(n + 1); }\nf(0);");
+        diagnostics.errors()[0].inner().msg.clone()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(msg, "Stack overflow.");
+}
+
+#[test]
+fn a_raised_stack_limit_allows_deeper_recursion() {
+    let msg = std::thread::Builder::new()
+        .stack_size(64 * 1024 * 1024)
+        .spawn(|| {
+            let mut lox = Lox::new();
+            lox.set_stack_limit(56 * 1024 * 1024);
+            lox.set_output(io::sink());
+            lox.eval("fun f(n) { if (n > 0) return f(n - 1); return n; }\nprint f(1000);")
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert!(msg.is_ok(), "{msg:?}");
+}
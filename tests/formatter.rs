@@ -0,0 +1,65 @@
+use lox_ast::Formatter;
+
+const MESSY: &str = "// header
+var   a=1+2*3 ;   // trailing
+var  b ;
+
+
+
+fun  add(x,y){return x+y;}
+class A<B{
+  init(){this.x=1.50;}
+  m(){ super.m( 1 ,2 ); }
+}
+if(a>1){print \"big\";}else print \"small\";
+for(;;){break;}
+";
+
+const CANONICAL: &str = "// header
+var a = 1 + 2 * 3; // trailing
+var b;
+
+fun add(x, y) {
+  return x + y;
+}
+class A < B {
+  init() {
+    this.x = 1.50;
+  }
+  m() {
+    super.m(1, 2);
+  }
+}
+if (a > 1) {
+  print \"big\";
+} else print \"small\";
+for (;;) {
+  break;
+}
+";
+
+#[test]
+fn formats_to_canonical_style() {
+    let formatted = Formatter::format(MESSY).expect("script should parse");
+    assert_eq!(formatted, CANONICAL);
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let formatted = Formatter::format(CANONICAL).expect("script should parse");
+    assert_eq!(formatted, CANONICAL);
+}
+
+#[test]
+fn scripts_that_dont_parse_are_rejected() {
+    assert!(Formatter::format("print ;").is_err());
+}
+
+#[test]
+fn keeps_consecutive_minus_signs_apart() {
+    let formatted = Formatter::format("print - -1;\nprint -(-1);\nprint !!true;\n");
+    assert_eq!(
+        formatted.expect("script should parse"),
+        "print - -1;\nprint -(-1);\nprint !!true;\n"
+    );
+}
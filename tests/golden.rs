@@ -0,0 +1,11 @@
+use std::path::Path;
+
+use lox_ast::harness;
+
+#[test]
+fn lox_scripts_match_expectations() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("lox");
+    let report = harness::run_dir(dir).expect("failed to read tests/lox");
+    assert!(!report.results.is_empty(), "no .lox scripts found");
+    assert_eq!(report.failed(), 0, "\n{report}");
+}
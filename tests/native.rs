@@ -0,0 +1,89 @@
+use lox_ast::{error, Error, Lox, LoxError, Object};
+
+fn repeat() -> Lox {
+    let mut lox = Lox::new();
+    lox.define_native("repeat", 2, |arguments| {
+        let text = String::try_from(&arguments[0])?;
+        let times = f64::try_from(&arguments[1])?;
+        Ok(Object::String(text.repeat(times as usize)))
+    });
+    lox
+}
+
+// the first error, which has to come from running the script
+fn runtime_error(lox: &mut Lox, source: &str) -> LoxError {
+    match lox.eval(source) {
+        Err(Error::Script(diagnostics)) => match diagnostics.errors().first() {
+            Some(error::Error::Runtime(err)) => err.clone(),
+            other => panic!("expected a runtime error, got {other:?}"),
+        },
+        other => panic!("expected a runtime error, got {other:?}"),
+    }
+}
+
+#[test]
+fn calls_a_host_function_with_converted_arguments() {
+    let mut lox = repeat();
+    lox.eval("var result = repeat(\"ab\", 3);").unwrap();
+    match lox.get_global("result") {
+        Some(Object::String(s)) => assert_eq!(s, "ababab"),
+        other => panic!("unexpected result {other:?}"),
+    }
+}
+
+#[test]
+fn reports_a_conversion_error_at_the_call_site() {
+    let mut lox = repeat();
+    let source = "var a = 1;\nprint repeat(\"ab\", \"three\");";
+    let err = runtime_error(&mut lox, source);
+    assert_eq!(err.msg, "Expected a number but got string.");
+    assert_eq!(err.line, 2);
+    assert_eq!(err.where_error, "at ')'");
+    assert_eq!(
+        err.span
+            .map(|span| &source[span.offset..span.offset + span.length]),
+        Some(")")
+    );
+}
+
+#[test]
+fn names_the_expected_type_of_each_conversion() {
+    let mut lox = Lox::new();
+    lox.define_native("truthy", 1, |arguments| {
+        bool::try_from(&arguments[0]).map(Object::Boolean)
+    });
+
+    let err = runtime_error(&mut lox, "truthy(nil);");
+    assert_eq!(err.msg, "Expected a boolean but got nil.");
+    let err = runtime_error(&mut repeat(), "repeat(1, 2);");
+    assert_eq!(err.msg, "Expected a string but got number.");
+}
+
+#[test]
+fn checks_the_arity_before_calling() {
+    let mut lox = repeat();
+    let err = runtime_error(&mut lox, "repeat(\"ab\");");
+    assert_eq!(err.msg, "Expected 2 arguments but got 1.");
+}
+
+struct BrokenPipe;
+
+impl std::io::Write for BrokenPipe {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn reports_a_failed_print_at_the_print_inside_a_call() {
+    let mut lox = Lox::new();
+    lox.set_output(BrokenPipe);
+    let err = runtime_error(&mut lox, "fun f() {\n  print 1;\n}\nf();");
+    assert!(err.msg.starts_with("Failed to print: "), "{}", err.msg);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.where_error, "at 'print'");
+}
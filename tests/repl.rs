@@ -0,0 +1,36 @@
+use lox_ast::Lox;
+
+#[test]
+fn complete_statements_are_complete() {
+    assert!(Lox::is_complete("print 1;\n"));
+    assert!(Lox::is_complete("{ print 1; }\n"));
+    assert!(Lox::is_complete(""));
+}
+
+#[test]
+fn waits_for_an_open_brace_to_close() {
+    assert!(!Lox::is_complete("fun f() {\n"));
+    assert!(!Lox::is_complete("class A {\n  m() {\n    print 1;\n  }\n"));
+    assert!(Lox::is_complete("fun f() {\n  print 1;\n}\n"));
+}
+
+#[test]
+fn waits_for_an_open_paren_to_close() {
+    assert!(!Lox::is_complete("print (1 +\n"));
+    assert!(!Lox::is_complete("f(1,\n"));
+    assert!(Lox::is_complete("f(1,\n2);\n"));
+}
+
+#[test]
+fn waits_for_an_unterminated_string() {
+    assert!(!Lox::is_complete("print \"one\n"));
+    assert!(Lox::is_complete("print \"one\ntwo\";\n"));
+}
+
+#[test]
+fn a_genuine_error_is_complete_so_it_gets_reported() {
+    assert!(Lox::is_complete("print ;\n"));
+    assert!(Lox::is_complete("var 1 = 2;\n"));
+    // a later error at the end is fallout from the first one
+    assert!(Lox::is_complete("{ 5\n}\n"));
+}
@@ -0,0 +1,35 @@
+use lox_ast::scanner::Scanner;
+
+fn scan(source: &str) -> Vec<String> {
+    let mut scanner = Scanner::new(source);
+    scanner.scan_tokens().expect("source should scan");
+    scanner.tokens.iter().map(|token| token.to_json()).collect()
+}
+
+#[test]
+fn writes_one_object_per_token() {
+    assert_eq!(
+        scan("print 1.5;"),
+        [
+            r#"{"type": "Print", "lexeme": "print", "literal": null, "line": 1, "column": 1, "offset": 0, "length": 5}"#,
+            r#"{"type": "Number", "lexeme": "1.5", "literal": 1.5, "line": 1, "column": 7, "offset": 6, "length": 3}"#,
+            r#"{"type": "SemiColon", "lexeme": ";", "literal": null, "line": 1, "column": 10, "offset": 9, "length": 1}"#,
+            r#"{"type": "Eof", "lexeme": "", "literal": null, "line": 1, "column": 11, "offset": 10, "length": 0}"#,
+        ]
+    );
+}
+
+#[test]
+fn escapes_quotes_backslashes_and_control_characters() {
+    let tokens = scan("\"a\\b\n\tc\u{1}\"");
+    assert_eq!(
+        tokens[0],
+        r#"{"type": "String", "lexeme": "\"a\\b\n\tc\u0001\"", "literal": "a\\b\n\tc\u0001", "line": 1, "column": 1, "offset": 0, "length": 9}"#
+    );
+}
+
+#[test]
+fn keeps_non_ascii_text_as_is() {
+    let tokens = scan("\"héllo\"");
+    assert!(tokens[0].contains(r#""literal": "héllo""#), "{}", tokens[0]);
+}